@@ -1,26 +1,55 @@
-use std::convert::TryFrom;
+use std::convert::{
+    TryFrom,
+    TryInto,
+};
 use std::str::FromStr;
 
+use mintbase_deps::common::time::{
+    now,
+    NearTime,
+};
 use mintbase_deps::common::{
     NFTContractMetadata,
+    StoreBootstrapArgs,
     StoreInitArgs,
+    StoreNamePolicy,
+    StoreRecord,
+    StoreSecurityMode,
+    StoreTemplate,
+    StoreUpgradeArgs,
 };
 use mintbase_deps::constants::{
     gas,
     storage_bytes,
-    storage_stake,
     NO_DEPOSIT,
     YOCTO_PER_BYTE,
 };
 use mintbase_deps::interfaces::factory_self;
-use mintbase_deps::logging::MbStoreDeployData;
+use mintbase_deps::logging::{
+    log_store_upgrade,
+    MbStoreDeployData,
+};
+use mintbase_deps::migrations::{
+    read_state_version,
+    write_state_version,
+    MintbaseStoreFactoryV1,
+};
 use mintbase_deps::near_sdk::borsh::{
     self,
     BorshDeserialize,
     BorshSerialize,
 };
-use mintbase_deps::near_sdk::collections::LookupSet;
-use mintbase_deps::near_sdk::json_types::U128;
+use mintbase_deps::near_sdk::collections::{
+    LookupMap,
+    LookupSet,
+    UnorderedMap,
+    UnorderedSet,
+};
+use mintbase_deps::near_sdk::json_types::{
+    Base58CryptoHash,
+    U128,
+    U64,
+};
 use mintbase_deps::near_sdk::{
     self,
     assert_one_yocto,
@@ -29,12 +58,18 @@ use mintbase_deps::near_sdk::{
     near_bindgen,
     AccountId,
     Balance,
+    CryptoHash,
     Promise,
     PublicKey,
 };
 use mintbase_deps::serde_json;
 // ------------------------------- constants -------------------------------- //
 
+/// Version of the state layout defined by `MintbaseStoreFactory`. Needs to be
+/// bumped whenever the layout changes, with the previous layout moving to
+/// `mintbase_deps::migrations`.
+pub const STATE_VERSION: u32 = 2;
+
 // ----------------------------- smart contract ----------------------------- //
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize)]
@@ -54,6 +89,45 @@ pub struct MintbaseStoreFactory {
     pub store_cost: u128,
     /// The public key to give a full access key to
     pub admin_public_key: PublicKey,
+    /// Store code that has been uploaded to this `Factory`, keyed by its
+    /// sha256 hash.
+    pub store_code: LookupMap<CryptoHash, Vec<u8>>,
+    /// Maps versions of the store code to the hash of the code.
+    pub store_code_versions: LookupMap<u32, CryptoHash>,
+    /// The most recently uploaded version of the store code. Version 0 refers
+    /// to the code that has been compiled into this `Factory`.
+    pub latest_store_version: u32,
+    /// The version of the store code that each `Store` is running, keyed by
+    /// store name.
+    pub store_versions: LookupMap<String, u32>,
+    /// Flavors of `Store`s that may be created, keyed by template ID.
+    pub store_templates: UnorderedMap<String, StoreTemplate>,
+    /// Enumerable registry of the `Store`s this `Factory` has produced, keyed
+    /// by store name. Stores created before the registry existed need to be
+    /// added via `register_legacy_stores`.
+    pub store_registry: UnorderedMap<String, StoreRecord>,
+    /// Names of the `Store`s in `store_registry`, indexed by store owner.
+    pub stores_by_owner: LookupMap<AccountId, UnorderedSet<String>>,
+    /// Names that cannot be used for `Store`s, e.g. because the accounts are
+    /// used by other Mintbase contracts.
+    pub reserved_names: UnorderedSet<String>,
+    /// Rules for the names of new `Store`s.
+    pub name_policy: StoreNamePolicy,
+    /// `Store`s that have been decommissioned, keyed by store name, with the
+    /// time of decommissioning. Retired stores remain in `store_registry`,
+    /// but are removed from `stores_by_owner` and hidden from the registry
+    /// views. Their names cannot be reused.
+    pub retired_stores: LookupMap<String, NearTime>,
+    /// Share (out of 10_000) of the deployment fee that is paid to the
+    /// referrer of a `Store`.
+    pub referral_percentage: u32,
+    /// Accounts that may be given as referrer on `create_store`.
+    pub eligible_referrers: UnorderedSet<AccountId>,
+    /// Security mode of new `Store`s, unless the creator chooses otherwise.
+    pub default_security_mode: StoreSecurityMode,
+    /// Security mode of each `Store`, keyed by store name. Stores that are
+    /// missing here hold a full access key for `admin_public_key`.
+    pub store_security_modes: LookupMap<String, StoreSecurityMode>,
 }
 
 // ----------------------- contract interface modules ----------------------- //
@@ -74,10 +148,15 @@ impl MintbaseStoreFactory {
         );
     }
 
-    /// Sufficient attached deposit is defined as enough to deploy a `Store`,
-    /// plus enough left over for the mintbase deployment cost.
-    pub fn assert_sufficient_attached_deposit(&self) {
-        let min = storage_bytes::STORE as u128 * self.storage_price_per_byte + self.mintbase_fee;
+    /// Sufficient attached deposit is defined as enough to deploy a `Store`
+    /// (from `template_id` if given), plus enough left over for the mintbase
+    /// deployment cost.
+    pub fn assert_sufficient_attached_deposit(
+        &self,
+        template_id: Option<String>,
+        store_name: Option<String>,
+    ) {
+        let min = self.get_minimum_attached_balance(template_id, store_name).0;
         assert!(
             env::attached_deposit() >= min,
             "Not enough attached deposit to complete store deployment. Need: {}, got: {}",
@@ -104,19 +183,171 @@ impl MintbaseStoreFactory {
         self.stores.contains(&store_id)
     }
 
+    /// Whether a `Store` with `name` could be created, i.e. it results in a
+    /// valid account ID, complies with the name policy, is not reserved and
+    /// not yet taken.
+    pub fn check_store_name_available(
+        &self,
+        name: String,
+    ) -> bool {
+        self.store_name_error(&name).is_none()
+    }
+
+    /// The reason why a `Store` with `name` cannot be created, if any.
+    pub fn get_store_name_error(
+        &self,
+        name: String,
+    ) -> Option<String> {
+        self.store_name_error(&name)
+    }
+
+    /// List the names that are reserved.
+    pub fn get_reserved_names(&self) -> Vec<String> {
+        self.reserved_names.to_vec()
+    }
+
+    /// Get the rules for the names of new `Store`s.
+    pub fn get_name_policy(&self) -> StoreNamePolicy {
+        self.name_policy.clone()
+    }
+
+    /// The number of `Store`s in the registry, including retired ones.
+    pub fn get_num_stores(&self) -> U64 {
+        self.store_registry.len().into()
+    }
+
+    /// List the `Store`s in the registry that have not been retired, `limit`
+    /// defaulting to 10.
+    pub fn get_stores(
+        &self,
+        from_index: Option<U64>,
+        limit: Option<u64>,
+    ) -> Vec<StoreRecord> {
+        self.store_registry
+            .iter()
+            .filter(|(name, _)| self.retired_stores.get(name).is_none())
+            .skip(from_index.map(|i| i.0).unwrap_or(0) as usize)
+            .take(limit.unwrap_or(10) as usize)
+            .map(|(_, record)| record)
+            .collect()
+    }
+
+    /// List the `Store`s in the registry that are owned by `owner_id`,
+    /// `limit` defaulting to 10.
+    pub fn get_stores_by_owner(
+        &self,
+        owner_id: AccountId,
+        from_index: Option<U64>,
+        limit: Option<u64>,
+    ) -> Vec<StoreRecord> {
+        let names = match self.stores_by_owner.get(&owner_id) {
+            Some(names) => names,
+            None => return vec![],
+        };
+        names
+            .iter()
+            .skip(from_index.map(|i| i.0).unwrap_or(0) as usize)
+            .take(limit.unwrap_or(10) as usize)
+            .flat_map(|name| self.store_registry.get(&name))
+            .collect()
+    }
+
+    /// When the `Store` with `name` has been decommissioned, if it has been.
+    pub fn get_store_retired_at(
+        &self,
+        name: String,
+    ) -> Option<NearTime> {
+        self.retired_stores.get(&name)
+    }
+
+    /// Get the registry entry of the `Store` with `name`, unless it has been
+    /// retired. See `get_store_retired_at` for retired stores.
+    pub fn get_store_info(
+        &self,
+        name: String,
+    ) -> Option<StoreRecord> {
+        if self.retired_stores.get(&name).is_some() {
+            return None;
+        }
+        self.store_registry.get(&name)
+    }
+
+    /// The share (out of 10_000) of the deployment fee that is paid to the
+    /// referrer of a `Store`.
+    pub fn get_referral_percentage(&self) -> u32 {
+        self.referral_percentage
+    }
+
+    /// List the accounts that may be given as referrer on `create_store`.
+    pub fn get_eligible_referrers(&self) -> Vec<AccountId> {
+        self.eligible_referrers.to_vec()
+    }
+
+    /// Check if `account_id` may be given as referrer on `create_store`.
+    pub fn check_is_eligible_referrer(
+        &self,
+        account_id: AccountId,
+    ) -> bool {
+        self.eligible_referrers.contains(&account_id)
+    }
+
     /// Get the `owner_id` of this `Factory`.
     pub fn get_owner(&self) -> &AccountId {
         &self.owner_id
     }
 
+    /// The Near-denominated price per byte of storage used by this
+    /// `Factory`.
+    pub fn get_storage_price_per_byte(&self) -> U128 {
+        self.storage_price_per_byte.into()
+    }
+
+    /// The storage stake for deploying a `Store` without template, as
+    /// derived from the storage price per byte.
+    pub fn get_store_cost(&self) -> U128 {
+        self.store_cost.into()
+    }
+
     /// Get the `mintbase_fee` of this `Factory`.
     pub fn get_mintbase_fee(&self) -> U128 {
         self.mintbase_fee.into()
     }
 
-    /// The sum of `mintbase_fee` and `STORE_STORAGE`.
-    pub fn get_minimum_attached_balance(&self) -> U128 {
-        (storage_bytes::STORE as u128 * self.storage_price_per_byte + self.mintbase_fee).into()
+    /// The sum of `mintbase_fee` and `STORE_STORAGE`. If `template_id` is
+    /// given, the store cost of that template is used instead of
+    /// `STORE_STORAGE`. If `store_name` is a premium name, the premium fee
+    /// is added.
+    pub fn get_minimum_attached_balance(
+        &self,
+        template_id: Option<String>,
+        store_name: Option<String>,
+    ) -> U128 {
+        let store_cost = match template_id {
+            Some(template_id) => self.get_store_template_internal(&template_id).store_cost.0,
+            None => self.store_cost,
+        };
+        let premium_fee = store_name
+            .map(|name| self.name_policy.premium_fee_for(&name))
+            .unwrap_or(0);
+        (store_cost + self.mintbase_fee + premium_fee).into()
+    }
+
+    /// The security mode of the `Store` with `name`, or `None` if this
+    /// `Factory` has not deployed such a store.
+    pub fn get_store_security_mode(
+        &self,
+        name: String,
+    ) -> Option<StoreSecurityMode> {
+        if !self.stores.contains(&name) {
+            return None;
+        }
+        Some(self.store_security_modes.get(&name).unwrap_or_default())
+    }
+
+    /// The security mode of new `Store`s, unless the creator chooses
+    /// otherwise.
+    pub fn get_default_security_mode(&self) -> StoreSecurityMode {
+        self.default_security_mode
     }
 
     /// The sum of `mintbase_fee` and `STORE_STORAGE`.
@@ -124,6 +355,255 @@ impl MintbaseStoreFactory {
         &self.admin_public_key
     }
 
+    /// The most recently uploaded version of the store code. Newly created
+    /// `Store`s will run this version.
+    pub fn get_latest_store_version(&self) -> u32 {
+        self.latest_store_version
+    }
+
+    /// The sha256 hash of the store code registered as `version`.
+    pub fn get_store_code_hash(
+        &self,
+        version: u32,
+    ) -> Option<Base58CryptoHash> {
+        self.store_code_versions
+            .get(&version)
+            .map(|hash| hash.into())
+    }
+
+    /// The version of the store code that the `Store` with `store_id` is
+    /// running.
+    pub fn get_store_version(
+        &self,
+        store_id: AccountId,
+    ) -> Option<u32> {
+        self.store_versions.get(&self.store_name(&store_id))
+    }
+
+    /// Get the store template with `template_id`.
+    pub fn get_store_template(
+        &self,
+        template_id: String,
+    ) -> Option<StoreTemplate> {
+        self.store_templates.get(&template_id)
+    }
+
+    /// List all store templates, including disabled ones.
+    pub fn list_store_templates(&self) -> Vec<(String, StoreTemplate)> {
+        self.store_templates.iter().collect()
+    }
+
+    /// Add a new store template or replace an existing one.
+    ///
+    /// Only the factory owner may call this function.
+    #[payable]
+    pub fn set_store_template(
+        &mut self,
+        template_id: String,
+        template: StoreTemplate,
+    ) {
+        self.assert_only_owner();
+        assert!(
+            template.code_version <= self.latest_store_version,
+            "Unknown store code version: {}",
+            template.code_version
+        );
+        if let Some(default_init_args) = template.default_init_args.as_ref() {
+            parse_init_args_object(default_init_args);
+        }
+        self.store_templates.insert(&template_id, &template);
+    }
+
+    /// Enable or disable the store template with `template_id`. Disabling a
+    /// template does not affect `Store`s that have been created from it.
+    ///
+    /// Only the factory owner may call this function.
+    #[payable]
+    pub fn set_store_template_enabled(
+        &mut self,
+        template_id: String,
+        enabled: bool,
+    ) {
+        self.assert_only_owner();
+        let mut template = self.get_store_template_internal(&template_id);
+        template.enabled = enabled;
+        self.store_templates.insert(&template_id, &template);
+    }
+
+    /// Remove the store template with `template_id`.
+    ///
+    /// Only the factory owner may call this function.
+    #[payable]
+    pub fn remove_store_template(
+        &mut self,
+        template_id: String,
+    ) {
+        self.assert_only_owner();
+        assert!(
+            self.store_templates.remove(&template_id).is_some(),
+            "Unknown store template: {}",
+            template_id
+        );
+    }
+
+    /// Reserve `names`, such that no `Store`s can be created with them.
+    ///
+    /// Only the factory owner may call this function.
+    #[payable]
+    pub fn add_reserved_names(
+        &mut self,
+        names: Vec<String>,
+    ) {
+        self.assert_only_owner();
+        for name in names {
+            self.reserved_names.insert(&name);
+        }
+    }
+
+    /// Release previously reserved `names`.
+    ///
+    /// Only the factory owner may call this function.
+    #[payable]
+    pub fn remove_reserved_names(
+        &mut self,
+        names: Vec<String>,
+    ) {
+        self.assert_only_owner();
+        for name in names {
+            self.reserved_names.remove(&name);
+        }
+    }
+
+    /// Replace the rules for the names of new `Store`s. Existing `Store`s are
+    /// not affected.
+    ///
+    /// Only the factory owner may call this function.
+    #[payable]
+    pub fn set_name_policy(
+        &mut self,
+        policy: StoreNamePolicy,
+    ) {
+        self.assert_only_owner();
+        assert!(
+            policy.min_length >= 1 && policy.min_length <= policy.max_length,
+            "Invalid name length bounds: {}..={}",
+            policy.min_length,
+            policy.max_length
+        );
+        self.name_policy = policy;
+    }
+
+    /// Set the share (out of 10_000) of the deployment fee that is paid to
+    /// the referrer of a `Store`.
+    ///
+    /// Only the factory owner may call this function.
+    #[payable]
+    pub fn set_referral_percentage(
+        &mut self,
+        percentage: u32,
+    ) {
+        self.assert_only_owner();
+        assert!(
+            percentage <= 10_000,
+            "Referral percentage cannot exceed 10000"
+        );
+        self.referral_percentage = percentage;
+    }
+
+    /// Allow `account_ids` to be given as referrer on `create_store`.
+    ///
+    /// Only the factory owner may call this function.
+    #[payable]
+    pub fn add_eligible_referrers(
+        &mut self,
+        account_ids: Vec<AccountId>,
+    ) {
+        self.assert_only_owner();
+        for account_id in account_ids {
+            self.eligible_referrers.insert(&account_id);
+        }
+    }
+
+    /// Disallow `account_ids` to be given as referrer on `create_store`.
+    ///
+    /// Only the factory owner may call this function.
+    #[payable]
+    pub fn remove_eligible_referrers(
+        &mut self,
+        account_ids: Vec<AccountId>,
+    ) {
+        self.assert_only_owner();
+        for account_id in account_ids {
+            self.eligible_referrers.remove(&account_id);
+        }
+    }
+
+    /// Add `Store`s that have been created before the registry existed to
+    /// the registry. The creation time of these stores is unknown.
+    ///
+    /// Only the factory owner may call this function.
+    #[payable]
+    pub fn register_legacy_stores(
+        &mut self,
+        records: Vec<StoreRecord>,
+    ) {
+        self.assert_only_owner();
+        for mut record in records {
+            let name = self.store_name(&record.store_id);
+            assert!(
+                self.stores.contains(&name),
+                "{} has not been deployed by this factory",
+                record.store_id
+            );
+            assert!(
+                self.store_registry.get(&name).is_none(),
+                "{} is already registered",
+                record.store_id
+            );
+            record.created_at = None;
+            record.code_version = self.store_versions.get(&name).unwrap_or(0);
+            self.insert_store_record(&name, &record);
+        }
+    }
+
+    /// Update the owner of a `Store` in the registry. This is called by the
+    /// `Store` itself when its ownership is transferred.
+    pub fn update_store_owner(
+        &mut self,
+        owner_id: AccountId,
+    ) {
+        let store_id = env::predecessor_account_id();
+        let name = self.store_name(&store_id);
+        let mut record = self
+            .store_registry
+            .get(&name)
+            .unwrap_or_else(|| panic!("{} is not registered", store_id));
+        self.remove_from_owner_index(&record.owner_id, &name);
+        record.owner_id = owner_id;
+        self.insert_store_record(&name, &record);
+    }
+
+    /// Mark a `Store` as retired in the registry. This is called by the
+    /// `Store` itself when it is decommissioned. Retiring a store again keeps
+    /// the original time of retirement, such that a `Store` whose account
+    /// could not be deleted may retry decommissioning.
+    pub fn retire_store(&mut self) {
+        let store_id = env::predecessor_account_id();
+        let name = self.store_name(&store_id);
+        assert!(
+            self.stores.contains(&name),
+            "{} has not been deployed by this factory",
+            store_id
+        );
+        if self.retired_stores.get(&name).is_some() {
+            return;
+        }
+        if let Some(record) = self.store_registry.get(&name) {
+            self.remove_from_owner_index(&record.owner_id, &name);
+        }
+        self.retired_stores.insert(&name, &now());
+    }
+
     /// The Near Storage price per byte has changed in the past, and may change in
     /// the future. This method may never be used.
     #[payable]
@@ -180,6 +660,49 @@ impl MintbaseStoreFactory {
         }
     }
 
+    /// Set the security mode for `Store`s that are created without
+    /// specifying one. Existing stores are not affected.
+    ///
+    /// Only the factory owner may call this function.
+    #[payable]
+    pub fn set_default_security_mode(
+        &mut self,
+        security_mode: StoreSecurityMode,
+    ) {
+        self.assert_only_owner();
+        self.default_security_mode = security_mode;
+    }
+
+    /// Pause minting on the `Store` with `store_id`, e.g. because the store
+    /// is compromised. The store owner may resume minting at any time.
+    ///
+    /// Only the factory owner may call this function, and only if the store
+    /// owner allows emergency actions by the factory.
+    #[payable]
+    pub fn emergency_pause_store(
+        &mut self,
+        store_id: AccountId,
+    ) -> Promise {
+        self.assert_only_owner();
+        let store_name = self.store_name(&store_id);
+        assert!(
+            self.stores.contains(&store_name),
+            "{} has not been deployed by this factory",
+            store_id
+        );
+        assert!(
+            self.retired_stores.get(&store_name).is_none(),
+            "{} has been retired",
+            store_id
+        );
+        Promise::new(store_id).function_call(
+            "emergency_pause_minting".to_string(),
+            b"{}".to_vec(),
+            NO_DEPOSIT,
+            gas::STORE_EMERGENCY_ACTION,
+        )
+    }
+
     /// Handle callback of store creation.
     #[private]
     pub fn on_create(
@@ -189,91 +712,337 @@ impl MintbaseStoreFactory {
         owner_id: AccountId,
         store_account_id: AccountId,
         attached_deposit: U128,
+        store_version: u32,
+        store_cost: U128,
+        forwarded_deposit: U128,
+        fee: U128,
+        template_id: Option<String>,
+        referrer_id: Option<AccountId>,
+        security_mode: StoreSecurityMode,
     ) {
         let attached_deposit: u128 = attached_deposit.into();
+        let store_cost: u128 = store_cost.into();
+        let forwarded_deposit: u128 = forwarded_deposit.into();
+        let fee: u128 = fee.into();
         if is_promise_success() {
             // pay out self and update contract state
             self.stores.insert(&metadata.name);
+            self.store_versions.insert(&metadata.name, &store_version);
+            self.store_security_modes
+                .insert(&metadata.name, &security_mode);
+            self.insert_store_record(
+                &metadata.name,
+                &StoreRecord {
+                    store_id: store_account_id.clone(),
+                    owner_id: owner_id.clone(),
+                    created_at: Some(now()),
+                    code_version: store_version,
+                    template_id,
+                    metadata: metadata.clone(),
+                },
+            );
+            // the referrer gets a share of the fee, the treasury keeps the
+            // rest of it and any overpayment
+            let referral_amount = referrer_id
+                .as_ref()
+                .map(|_| fee * self.referral_percentage as u128 / 10_000);
             env::log_str(
                 &MbStoreDeployData {
                     contract_metadata: metadata,
                     owner_id: owner_id.to_string(),
                     store_id: store_account_id.to_string(),
+                    referrer_id: referrer_id.as_ref().map(|id| id.to_string()),
+                    referral_amount: referral_amount.map(|amount| amount.into()),
                 }
                 .serialize_event(),
             );
-            Promise::new(self.owner_id.to_string().parse().unwrap())
-                .transfer(attached_deposit - self.store_cost);
+            if let (Some(referrer_id), Some(amount)) = (referrer_id, referral_amount) {
+                if amount > 0 {
+                    Promise::new(referrer_id).transfer(amount);
+                }
+            }
+            Promise::new(self.owner_id.to_string().parse().unwrap()).transfer(
+                attached_deposit - store_cost - forwarded_deposit - referral_amount.unwrap_or(0),
+            );
             // #[cfg(feature = "panic-test")]
             // env::panic_str("event.near_json_event().as_str()");
         } else {
-            // Refunding store cost creation to the store creator
-            Promise::new(store_creator_id).transfer(attached_deposit - self.store_cost);
+            // Refunding store cost creation to the store creator, including
+            // the deposit that was forwarded for the initial mint
+            Promise::new(store_creator_id).transfer(attached_deposit - store_cost);
             env::log_str("failed store deployment");
         }
     }
 
+    /// Handle callback of store upgrade.
+    #[private]
+    pub fn on_upgrade(
+        &mut self,
+        store_id: AccountId,
+        version: u32,
+        requested_by: AccountId,
+    ) {
+        if is_promise_success() {
+            let name = self.store_name(&store_id);
+            self.store_versions.insert(&name, &version);
+            if let Some(mut record) = self.store_registry.get(&name) {
+                record.code_version = version;
+                self.store_registry.insert(&name, &record);
+            }
+            log_store_upgrade(&store_id, version, &requested_by);
+        } else {
+            env::log_str("failed store upgrade");
+        }
+    }
+
     #[init(ignore_state)]
     pub fn new() -> Self {
         assert!(!env::state_exists());
         let storage_price_per_byte = YOCTO_PER_BYTE; // 10^19
+        write_state_version(STATE_VERSION);
         Self {
             stores: LookupSet::new(b"t".to_vec()),
             mintbase_fee: 0, // 0 by default
             owner_id: env::predecessor_account_id(),
             storage_price_per_byte,
-            store_cost: storage_stake::STORE,
+            store_cost: storage_price_per_byte * storage_bytes::STORE as u128,
             admin_public_key: env::signer_account_pk(),
+            store_code: LookupMap::new(b"c".to_vec()),
+            store_code_versions: LookupMap::new(b"v".to_vec()),
+            latest_store_version: 0,
+            store_versions: LookupMap::new(b"d".to_vec()),
+            store_templates: UnorderedMap::new(b"p".to_vec()),
+            store_registry: UnorderedMap::new(b"r".to_vec()),
+            stores_by_owner: LookupMap::new(b"o".to_vec()),
+            reserved_names: default_reserved_names(),
+            name_policy: StoreNamePolicy::default(),
+            retired_stores: LookupMap::new(b"x".to_vec()),
+            referral_percentage: 0,
+            eligible_referrers: UnorderedSet::new(b"q".to_vec()),
+            default_security_mode: StoreSecurityMode::FactoryCalls,
+            store_security_modes: LookupMap::new(b"k".to_vec()),
         }
     }
 
     /// Contract metadata and methods in the API may be updated. All other
     /// elements of the state should be copied over. This method may only be
     /// called by the holder of the contract private key.
+    ///
+    /// The state is migrated from whichever layout version is currently
+    /// stored to `STATE_VERSION`.
     #[private]
     #[init(ignore_state)]
     pub fn migrate() -> Self {
-        let old = env::state_read().expect("ohno ohno state");
-        Self { ..old }
+        let version = read_state_version();
+        assert!(
+            version <= STATE_VERSION,
+            "Cannot migrate from state version {} to older version {}",
+            version,
+            STATE_VERSION
+        );
+        let factory = Self::read(version);
+        write_state_version(STATE_VERSION);
+        factory
+    }
+
+    /// Upload a new version of the store code. The code is expected as raw
+    /// (non-JSON) input. The attached deposit needs to cover the storage of
+    /// the code. Returns the version under which the code has been
+    /// registered.
+    ///
+    /// Only the factory owner may call this function.
+    #[payable]
+    pub fn upload_store_code(&mut self) -> u32 {
+        assert_eq!(
+            env::predecessor_account_id(),
+            self.owner_id,
+            "Only contract owner can call this method"
+        );
+        let code = env::input().expect("No store code provided");
+        let required_deposit = code.len() as u128 * self.storage_price_per_byte;
+        assert!(
+            env::attached_deposit() >= required_deposit,
+            "Not enough attached deposit to store the code. Need: {}, got: {}",
+            required_deposit,
+            env::attached_deposit()
+        );
+
+        let hash: CryptoHash = env::sha256(&code).try_into().unwrap();
+        self.latest_store_version += 1;
+        self.store_code.insert(&hash, &code);
+        self.store_code_versions
+            .insert(&self.latest_store_version, &hash);
+        self.latest_store_version
+    }
+
+    /// Deploy `version` of the store code on the `Store` with `store_id`,
+    /// and migrate the state of the `Store`.
+    ///
+    /// Only the store owner, or the factory owner if the store owner has
+    /// allowed it, may upgrade a `Store`. Stores can only be upgraded to
+    /// newer versions.
+    #[payable]
+    pub fn upgrade_store(
+        &mut self,
+        store_id: AccountId,
+        version: u32,
+    ) -> Promise {
+        assert_one_yocto();
+        let store_name = self.store_name(&store_id);
+        assert!(
+            self.stores.contains(&store_name),
+            "{} has not been deployed by this factory",
+            store_id
+        );
+        assert!(
+            self.retired_stores.get(&store_name).is_none(),
+            "{} has been retired",
+            store_id
+        );
+        let current_version = self.store_versions.get(&store_name).unwrap_or(0);
+        assert!(
+            version > current_version,
+            "{} already runs version {} of the store code",
+            store_id,
+            current_version
+        );
+
+        let requested_by = env::predecessor_account_id();
+        let args = StoreUpgradeArgs {
+            code: self.get_store_code_internal(version),
+            version,
+            by_factory_owner: requested_by == self.owner_id,
+            requested_by: requested_by.clone(),
+        }
+        .try_to_vec()
+        .unwrap();
+
+        Promise::new(store_id.clone())
+            .function_call("upgrade".to_string(), args, NO_DEPOSIT, gas::UPGRADE_STORE)
+            .then(factory_self::on_upgrade(
+                store_id,
+                version,
+                requested_by,
+                env::current_account_id(),
+                NO_DEPOSIT,
+                gas::ON_UPGRADE_CALLBACK,
+            ))
     }
 
     /// `create_store` checks that the attached deposit is sufficient before
     /// parsing the given store_id, validating no such store subaccount exists yet
     /// and generates a new store from the store metadata.
+    ///
+    /// If `template_id` is given, the store is created from that template,
+    /// and `template_args` are passed on to the store initialization.
+    /// Otherwise, the store runs the latest version of the store code.
+    ///
+    /// `bootstrap` allows setting up minters, settings and default royalty,
+    /// and minting the first tokens within the same transaction. If tokens
+    /// are minted, any deposit exceeding the minimum attached balance is
+    /// forwarded to the `Store` to cover their storage.
+    ///
+    /// If an eligible `referrer_id` is given, they receive a share of the
+    /// deployment fee, i.e. the `mintbase_fee` and premium fee for the name.
+    /// Overpayment is not shared.
+    ///
+    /// `security_mode` defaults to `default_security_mode`. Stores created
+    /// with `FactoryCalls` receive no full access key, and allow factory
+    /// upgrades and emergency actions unless `bootstrap` or template settings
+    /// say otherwise.
     #[payable]
     pub fn create_store(
         &mut self,
         metadata: NFTContractMetadata,
         owner_id: AccountId,
+        template_id: Option<String>,
+        template_args: Option<serde_json::Value>,
+        bootstrap: Option<StoreBootstrapArgs>,
+        referrer_id: Option<AccountId>,
+        security_mode: Option<StoreSecurityMode>,
     ) -> Promise {
-        self.assert_sufficient_attached_deposit();
-        self.assert_no_store_with_id(metadata.name.clone());
-        assert_ne!(&metadata.name, "market"); // marketplace lives here
-        assert_ne!(&metadata.name, "loan"); // loan lives here
+        if let Some(error) = self.store_name_error(&metadata.name) {
+            env::panic_str(&error);
+        }
+        if let Some(referrer_id) = referrer_id.as_ref() {
+            assert!(
+                self.eligible_referrers.contains(referrer_id),
+                "{} is not an eligible referrer",
+                referrer_id
+            );
+        }
+        self.assert_sufficient_attached_deposit(template_id.clone(), Some(metadata.name.clone()));
+        let security_mode = security_mode.unwrap_or(self.default_security_mode);
+        let bootstrap = bootstrap.unwrap_or_default();
+        let (forwarded_deposit, init_gas) = match bootstrap.initial_mint {
+            Some(_) => {
+                let min = self
+                    .get_minimum_attached_balance(template_id.clone(), Some(metadata.name.clone()))
+                    .0;
+                (env::attached_deposit() - min, gas::CREATE_STORE_WITH_MINT)
+            },
+            None => (0, gas::CREATE_STORE),
+        };
+        let fee = self.mintbase_fee + self.name_policy.premium_fee_for(&metadata.name);
         let metadata = NFTContractMetadata::new(metadata);
-        let init_args = serde_json::to_vec(&StoreInitArgs {
+        let mut init_args = serde_json::to_value(&StoreInitArgs {
             metadata: metadata.clone(),
             owner_id: owner_id.clone(),
+            bootstrap,
         })
         .unwrap();
+        let (store_version, store_cost) = match template_id.as_ref() {
+            Some(template_id) => {
+                let template = self.get_store_template_internal(template_id);
+                assert!(
+                    template.enabled,
+                    "Store template {} is disabled",
+                    template_id
+                );
+                init_args = merge_template_args(&template, template_args, init_args);
+                (template.code_version, template.store_cost.0)
+            },
+            None => {
+                assert!(
+                    template_args.is_none(),
+                    "Template args require a template ID"
+                );
+                (self.latest_store_version, self.store_cost)
+            },
+        };
+        if security_mode == StoreSecurityMode::FactoryCalls {
+            // without an admin key, factory calls are the only way to recover
+            allow_factory_calls(&mut init_args);
+        }
+        let init_args = serde_json::to_vec(&init_args).unwrap();
         // StoreId is only the subaccount. store_account_id is the full near qualified name.
         // Note, validity checked in `NFTContractMetadata::new;` above.
 
         let store_account_id =
             AccountId::from_str(&*format!("{}.{}", metadata.name, env::current_account_id()))
                 .unwrap();
-        Promise::new(store_account_id.clone())
+        let mut promise = Promise::new(store_account_id.clone())
             .create_account()
-            .transfer(self.store_cost)
-            .add_full_access_key(self.admin_public_key.clone())
-            .deploy_contract(include_bytes!("../../wasm/store.wasm").to_vec())
-            .function_call("new".to_string(), init_args, 0, gas::CREATE_STORE)
+            .transfer(store_cost + forwarded_deposit);
+        if security_mode == StoreSecurityMode::AdminKey {
+            promise = promise.add_full_access_key(self.admin_public_key.clone());
+        }
+        promise
+            .deploy_contract(self.get_store_code_internal(store_version))
+            .function_call("new".to_string(), init_args, 0, init_gas)
             .then(factory_self::on_create(
                 env::predecessor_account_id(),
                 metadata,
                 owner_id,
                 store_account_id,
                 env::attached_deposit().into(),
+                store_version,
+                store_cost.into(),
+                forwarded_deposit.into(),
+                fee.into(),
+                template_id,
+                referrer_id,
+                security_mode,
                 env::current_account_id(),
                 NO_DEPOSIT,
                 gas::ON_CREATE_CALLBACK,
@@ -281,6 +1050,240 @@ impl MintbaseStoreFactory {
     }
 }
 
+impl MintbaseStoreFactory {
+    /// Read the state as the current layout, migrating it from the layout
+    /// indicated by `version` if necessary.
+    fn read(version: u32) -> Self {
+        if version < STATE_VERSION {
+            MintbaseStoreFactoryV1::read(version).into()
+        } else {
+            env::state_read().expect("ohno ohno state")
+        }
+    }
+
+    /// Get the code for `version` of the store. Version 0 refers to the code
+    /// that has been compiled into this `Factory`.
+    fn get_store_code_internal(
+        &self,
+        version: u32,
+    ) -> Vec<u8> {
+        if version == 0 {
+            return include_bytes!("../../wasm/store.wasm").to_vec();
+        }
+        let hash = self
+            .store_code_versions
+            .get(&version)
+            .unwrap_or_else(|| panic!("Unknown store code version: {}", version));
+        self.store_code.get(&hash).unwrap()
+    }
+
+    fn get_store_template_internal(
+        &self,
+        template_id: &str,
+    ) -> StoreTemplate {
+        self.store_templates
+            .get(&template_id.to_string())
+            .unwrap_or_else(|| panic!("Unknown store template: {}", template_id))
+    }
+
+    /// Check `name` against the rules for store names, returning the first
+    /// violation.
+    fn store_name_error(
+        &self,
+        name: &str,
+    ) -> Option<String> {
+        let store_account = format!("{}.{}", name, env::current_account_id());
+        if !env::is_valid_account_id(store_account.as_bytes()) {
+            return Some("Invalid character in store id".to_string());
+        }
+        if name.contains('.') {
+            return Some("Store name cannot contain dots".to_string());
+        }
+        if name.len() < self.name_policy.min_length as usize {
+            return Some(format!(
+                "Store name must have at least {} characters",
+                self.name_policy.min_length
+            ));
+        }
+        if name.len() > self.name_policy.max_length as usize {
+            return Some(format!(
+                "Store name cannot have more than {} characters",
+                self.name_policy.max_length
+            ));
+        }
+        if self.reserved_names.contains(&name.to_string())
+            || self.name_policy.is_reserved_pattern(name)
+        {
+            return Some(format!("Store name {} is reserved", name));
+        }
+        if self.check_contains_store(name.to_string()) {
+            return Some("Store with that ID already exists".to_string());
+        }
+        None
+    }
+
+    /// Insert `record` into the registry and the owner index.
+    fn insert_store_record(
+        &mut self,
+        name: &String,
+        record: &StoreRecord,
+    ) {
+        self.store_registry.insert(name, record);
+        let mut names = self
+            .stores_by_owner
+            .get(&record.owner_id)
+            .unwrap_or_else(|| {
+                let mut prefix: Vec<u8> = vec![b'w'];
+                prefix.extend_from_slice(record.owner_id.as_bytes());
+                UnorderedSet::new(prefix)
+            });
+        names.insert(name);
+        self.stores_by_owner.insert(&record.owner_id, &names);
+    }
+
+    fn remove_from_owner_index(
+        &mut self,
+        owner_id: &AccountId,
+        name: &String,
+    ) {
+        if let Some(mut names) = self.stores_by_owner.get(owner_id) {
+            names.remove(name);
+            if names.is_empty() {
+                self.stores_by_owner.remove(owner_id);
+            } else {
+                self.stores_by_owner.insert(owner_id, &names);
+            }
+        }
+    }
+
+    /// Get the name of a `Store` from its account ID, which needs to be a
+    /// direct subaccount of this `Factory`.
+    fn store_name(
+        &self,
+        store_id: &AccountId,
+    ) -> String {
+        let suffix = format!(".{}", env::current_account_id());
+        store_id
+            .as_str()
+            .strip_suffix(&suffix)
+            .filter(|name| !name.contains('.'))
+            .unwrap_or_else(|| panic!("{} is not a store of this factory", store_id))
+            .to_string()
+    }
+}
+
+// ---------------------------- state migrations ---------------------------- //
+
+impl From<MintbaseStoreFactoryV1> for MintbaseStoreFactory {
+    fn from(old: MintbaseStoreFactoryV1) -> Self {
+        Self {
+            stores: old.stores,
+            mintbase_fee: old.mintbase_fee,
+            owner_id: old.owner_id,
+            storage_price_per_byte: old.storage_price_per_byte,
+            store_cost: old.store_cost,
+            admin_public_key: old.admin_public_key,
+            store_code: LookupMap::new(b"c".to_vec()),
+            store_code_versions: LookupMap::new(b"v".to_vec()),
+            latest_store_version: 0,
+            store_versions: LookupMap::new(b"d".to_vec()),
+            store_templates: UnorderedMap::new(b"p".to_vec()),
+            // existing stores are added via `register_legacy_stores`
+            store_registry: UnorderedMap::new(b"r".to_vec()),
+            stores_by_owner: LookupMap::new(b"o".to_vec()),
+            reserved_names: default_reserved_names(),
+            name_policy: StoreNamePolicy::default(),
+            retired_stores: LookupMap::new(b"x".to_vec()),
+            referral_percentage: 0,
+            eligible_referrers: UnorderedSet::new(b"q".to_vec()),
+            // existing deployments keep their behaviour until the owner
+            // switches the default
+            default_security_mode: StoreSecurityMode::AdminKey,
+            store_security_modes: LookupMap::new(b"k".to_vec()),
+        }
+    }
+}
+
+/// The names that have been hardcoded as reserved before they became
+/// configurable.
+fn default_reserved_names() -> UnorderedSet<String> {
+    let mut reserved_names = UnorderedSet::new(b"n".to_vec());
+    reserved_names.insert(&"market".to_string()); // marketplace lives here
+    reserved_names.insert(&"loan".to_string()); // loan lives here
+    reserved_names
+}
+
+// ----------------------------- store templates ---------------------------- //
+
+/// Parse init args given as JSON string, which need to be a JSON object.
+fn parse_init_args_object(args: &str) -> serde_json::Map<String, serde_json::Value> {
+    match serde_json::from_str(args) {
+        Ok(serde_json::Value::Object(args)) => args,
+        _ => panic!("Init args must be a JSON object"),
+    }
+}
+
+/// Combine the default init args of `template` with the `template_args` given
+/// by the store creator, where the latter take precedence. The `base_args`
+/// (metadata and owner) cannot be overridden. Settings are combined field by
+/// field, so settings that are not given keep their template value.
+fn merge_template_args(
+    template: &StoreTemplate,
+    template_args: Option<serde_json::Value>,
+    base_args: serde_json::Value,
+) -> serde_json::Value {
+    let mut args = template
+        .default_init_args
+        .as_deref()
+        .map(parse_init_args_object)
+        .unwrap_or_default();
+    match template_args {
+        Some(serde_json::Value::Object(template_args)) => {
+            extend_init_args(&mut args, template_args)
+        },
+        Some(_) => panic!("Init args must be a JSON object"),
+        None => {},
+    }
+    if let serde_json::Value::Object(base_args) = base_args {
+        extend_init_args(&mut args, base_args);
+    }
+    serde_json::Value::Object(args)
+}
+
+/// Extend the init `args` with `other`. Both sets of `settings` are combined,
+/// where only the fields that are set in `other` take precedence.
+fn extend_init_args(
+    args: &mut serde_json::Map<String, serde_json::Value>,
+    other: serde_json::Map<String, serde_json::Value>,
+) {
+    for (key, value) in other {
+        match (args.get_mut(&key), value) {
+            (Some(serde_json::Value::Object(settings)), serde_json::Value::Object(other))
+                if key == "settings" =>
+            {
+                settings.extend(other.into_iter().filter(|(_, value)| !value.is_null()));
+            },
+            (_, value) => {
+                args.insert(key, value);
+            },
+        }
+    }
+}
+
+/// Allow factory upgrades and emergency actions in the settings of the init
+/// `args`, unless these settings already say otherwise.
+fn allow_factory_calls(args: &mut serde_json::Value) {
+    let settings = &mut args["settings"];
+    if !settings.is_object() {
+        *settings = serde_json::Value::Object(Default::default());
+    }
+    for flag in ["allow_factory_upgrades", "allow_factory_emergency_actions"] {
+        if settings[flag].is_null() {
+            settings[flag] = serde_json::Value::Bool(true);
+        }
+    }
+}
+
 // ------------------------ impls on external types ------------------------- //
 // TODO: Why the trait? -> to be able to impl it in this crate
 pub trait New {
@@ -294,7 +1297,10 @@ impl New for NFTContractMetadata {
             env::is_valid_account_id(store_account.as_bytes()),
             "Invalid character in store id"
         );
-        assert!(args.symbol.len() <= 6);
+        assert!(
+            args.symbol.len() <= 6,
+            "Store symbol cannot have more than 6 characters"
+        );
 
         Self {
             spec: args.spec,
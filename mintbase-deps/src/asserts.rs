@@ -96,12 +96,14 @@ macro_rules! assert_token_owned_by_predecessor {
     };
 }
 
+/// `$approvals` are the approvals of `$token`, as a map from account IDs to
+/// approval IDs.
 #[macro_export]
 macro_rules! assert_token_owned_or_approved {
-    ($token:expr, $account:expr, $approval_id:expr) => {
+    ($token:expr, $approvals:expr, $account:expr, $approval_id:expr) => {
         if !$token.is_owned_by($account) {
             let src = format!("{}, {}:{}", file!(), line!(), column!());
-            match ($token.approvals.get($account), $approval_id) {
+            match ($approvals.get($account), $approval_id) {
                 (_, None) => {
                     $crate::near_panic!("Disallowing approvals without approval ID! ({})", src)
                 },
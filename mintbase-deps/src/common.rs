@@ -1,11 +1,21 @@
+pub mod approval_scope;
 // pub mod loan;
+pub mod migration_progress;
+pub mod mint_job;
 // pub mod owner;
 pub mod payouts;
 pub mod safe_fraction;
 pub mod sale_args;
 // pub mod storage;
+pub mod store_change;
+pub mod store_info;
 pub mod store_init_args;
 pub mod store_metadata;
+pub mod store_name_policy;
+pub mod store_record;
+pub mod store_security_mode;
+pub mod store_template;
+pub mod store_upgrade_args;
 pub mod time;
 // pub mod token;
 pub mod token_key;
@@ -13,7 +23,10 @@ pub mod token_listing;
 pub mod token_metadata;
 pub mod token_offer;
 
+pub use approval_scope::ApprovalScope;
 // pub use loan::Loan;
+pub use migration_progress::MigrationProgress;
+pub use mint_job::MintJob;
 // pub use owner::Owner;
 pub use payouts::{
     OwnershipFractions,
@@ -33,11 +46,26 @@ pub use sale_args::SaleArgs;
 //     StorageCosts,
 //     StorageCostsMarket,
 // };
-pub use store_init_args::StoreInitArgs;
+pub use store_change::{
+    PendingStoreChange,
+    StoreChange,
+};
+pub use store_info::StoreInfo;
+pub use store_init_args::{
+    StoreBootstrapArgs,
+    StoreInitArgs,
+    StoreInitialMint,
+    StoreSettings,
+};
 pub use store_metadata::{
     NFTContractMetadata,
     NonFungibleContractMetadata,
 };
+pub use store_name_policy::StoreNamePolicy;
+pub use store_record::StoreRecord;
+pub use store_security_mode::StoreSecurityMode;
+pub use store_template::StoreTemplate;
+pub use store_upgrade_args::StoreUpgradeArgs;
 pub use time::{
     NearTime,
     TimeUnit,
@@ -0,0 +1,44 @@
+use near_sdk::borsh::{
+    self,
+    BorshDeserialize,
+    BorshSerialize,
+};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{
+    Deserialize,
+    Serialize,
+};
+
+use crate::common::NearTime;
+
+/// Optional constraints on an approval, given to `nft_approve` or
+/// `nft_batch_approve`. Approvals without constraints are valid until the
+/// token is transferred or the approval is revoked.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "wasm", derive(BorshDeserialize, BorshSerialize))]
+pub struct ApprovalScope {
+    /// The approval may not be used from this point in time on.
+    #[serde(default)]
+    pub expires_at: Option<NearTime>,
+    /// The approval may only be used by `nft_transfer_payout`, with a
+    /// `balance` of at least this amount.
+    #[serde(default)]
+    pub min_price: Option<U128>,
+    /// The approval is consumed by the first transfer it authorizes. Most
+    /// transfers clear all approvals of the token anyway, so this only makes
+    /// a difference for `nft_transfer_call` if the receiver does not keep the
+    /// token, which then stays with its owner. Without this flag, the
+    /// approval would remain usable in that case.
+    #[serde(default)]
+    pub single_use: bool,
+}
+
+impl ApprovalScope {
+    /// Whether the expiry of this approval has passed.
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .as_ref()
+            .map(|expires_at| !expires_at.is_before_timeout())
+            .unwrap_or(false)
+    }
+}
@@ -0,0 +1,20 @@
+use near_sdk::borsh::{
+    self,
+    BorshDeserialize,
+    BorshSerialize,
+};
+use near_sdk::json_types::U64;
+use near_sdk::serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// Progress of the backfill that follows a state migration of the `Store`.
+/// Indexes that cannot be built within the gas of the `migrate` call are
+/// filled in chunks via `continue_migration`, in order of token IDs.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "wasm", derive(BorshDeserialize, BorshSerialize))]
+pub struct MigrationProgress {
+    /// The next token ID to backfill.
+    pub cursor: U64,
+}
@@ -0,0 +1,40 @@
+use near_sdk::borsh::{
+    self,
+    BorshDeserialize,
+    BorshSerialize,
+};
+use near_sdk::json_types::U64;
+use near_sdk::serde::{
+    Deserialize,
+    Serialize,
+};
+use near_sdk::AccountId;
+
+use crate::common::SplitOwners;
+
+/// A mint of many copies of a token that is too large for a single
+/// transaction. The storage for all copies is paid when the job is created,
+/// after which anyone may mint the tokens in chunks via `continue_mint_job`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "wasm", derive(BorshDeserialize, BorshSerialize))]
+pub struct MintJob {
+    /// Identifies this job on the `Store`.
+    pub id: U64,
+    /// The minter that created the job, recorded as minter on each token.
+    pub minter_id: AccountId,
+    /// The account that receives the tokens.
+    pub owner_id: AccountId,
+    /// Metadata shared by all tokens of the job.
+    pub metadata_id: U64,
+    /// Royalty shared by all tokens of the job, if any.
+    pub royalty_id: Option<U64>,
+    /// Split ownership of each token of the job, if any.
+    pub split_owners: Option<SplitOwners>,
+    /// The token IDs `first_token_id` to `first_token_id + total - 1` are
+    /// reserved for the job, and minted in this order.
+    pub first_token_id: U64,
+    /// The number of tokens the job mints in total.
+    pub total: U64,
+    /// The number of tokens that have been minted so far.
+    pub minted: U64,
+}
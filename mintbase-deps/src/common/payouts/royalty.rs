@@ -78,7 +78,8 @@ impl Royalty {
 }
 
 /// Unparsed pre-image of a Royalty struct. Used in `Store::mint_tokens`.
-#[derive(Clone, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "wasm", derive(BorshDeserialize, BorshSerialize))]
 pub struct RoyaltyArgs {
     pub split_between: SplitBetweenUnparsed,
     pub percentage: u32,
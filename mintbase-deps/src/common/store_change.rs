@@ -0,0 +1,65 @@
+use near_sdk::borsh::{
+    self,
+    BorshDeserialize,
+    BorshSerialize,
+};
+use near_sdk::json_types::{
+    Base58CryptoHash,
+    U128,
+    U64,
+};
+use near_sdk::serde::{
+    Deserialize,
+    Serialize,
+};
+use near_sdk::AccountId;
+
+use crate::common::{
+    NearTime,
+    RoyaltyArgs,
+};
+
+/// Administrative changes to a `Store` that are subject to the store
+/// timelock. If the store has a non-zero admin delay, these changes need to
+/// be scheduled, and may only be executed once the delay has passed.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "wasm", derive(BorshDeserialize, BorshSerialize))]
+pub enum StoreChange {
+    /// Equivalent to calling `set_allow_moves`.
+    SetAllowMoves(bool),
+    /// Equivalent to calling `set_storage_price_per_byte`.
+    SetStoragePricePerByte(U128),
+    /// Equivalent to calling `revoke_minter`.
+    RevokeMinter(AccountId),
+    /// Change the delay (in nanoseconds) of the timelock itself.
+    SetAdminDelay(U64),
+    /// Equivalent to calling `set_max_royalty_percentage`.
+    SetMaxRoyaltyPercentage(u32),
+    /// Equivalent to calling `set_minting_paused`.
+    SetMintingPaused(bool),
+    /// Equivalent to calling `set_default_royalty`.
+    SetDefaultRoyalty(Option<RoyaltyArgs>),
+    /// Allow upgrading the store to the code with `code_hash`. Unlike the
+    /// other changes, this is executed by `upgrade_store` on the `Factory`,
+    /// which provides the code.
+    Upgrade { code_hash: Base58CryptoHash },
+    /// Equivalent to calling `set_allow_factory_upgrades`.
+    SetAllowFactoryUpgrades(bool),
+    /// Equivalent to calling `set_allow_factory_emergency_actions`.
+    SetAllowFactoryEmergencyActions(bool),
+}
+
+/// A `StoreChange` that has been scheduled by the store owner, but has not
+/// yet been executed or cancelled.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "wasm", derive(BorshDeserialize, BorshSerialize))]
+pub struct PendingStoreChange {
+    /// Identifies this change for execution or cancellation.
+    pub id: U64,
+    /// The change to be applied on execution.
+    pub change: StoreChange,
+    /// When the change has been scheduled.
+    pub scheduled_at: NearTime,
+    /// The change may not be executed before this point in time.
+    pub executable_at: NearTime,
+}
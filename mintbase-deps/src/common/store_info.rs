@@ -0,0 +1,53 @@
+use near_sdk::json_types::{
+    U128,
+    U64,
+};
+use near_sdk::serde::{
+    Deserialize,
+    Serialize,
+};
+use near_sdk::AccountId;
+
+use crate::common::StoreSettings;
+
+/// Configuration and counters of a `Store`, as returned by `get_info`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StoreInfo {
+    /// The owner of the `Store`.
+    pub owner_id: AccountId,
+    /// Number of accounts that may mint on the `Store`, including the owner.
+    pub minter_count: u64,
+    /// Number of tokens that have been minted, including burned tokens.
+    pub tokens_minted: U64,
+    /// Number of tokens that have been burned.
+    pub tokens_burned: U64,
+    /// Number of approvals that have been granted. Used to generate approval
+    /// IDs.
+    pub num_approved: U64,
+    /// Current policy settings of the `Store`.
+    pub settings: StoreSettings,
+    /// Delay in nanoseconds between scheduling and executing administrative
+    /// changes.
+    pub admin_delay: U64,
+    /// Number of administrative changes that are currently scheduled.
+    pub num_pending_changes: u64,
+    /// Price per byte of storage that the `Store` uses for its cost
+    /// calculations.
+    pub storage_price_per_byte: U128,
+    /// Bytes of storage that the `Store` account currently uses.
+    pub storage_usage: U64,
+    /// Balance of the `Store` account.
+    pub balance: U128,
+    /// Balance that is neither required to cover storage nor reserved for
+    /// mint jobs.
+    pub free_storage_stake: U128,
+    /// Balance reserved for the tokens that unfinished mint jobs have yet to
+    /// mint.
+    pub reserved_mint_job_deposit: U128,
+    /// Number of mint jobs that have not been completed.
+    pub num_pending_mint_jobs: u64,
+    /// Free balance that needs to remain after minting.
+    pub minimum_free_storage_stake: U128,
+    /// Version of the state layout.
+    pub state_version: u32,
+}
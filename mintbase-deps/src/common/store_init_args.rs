@@ -9,11 +9,68 @@ use near_sdk::serde::{
 };
 use near_sdk::AccountId;
 
-use crate::common::NFTContractMetadata;
+use crate::common::{
+    NFTContractMetadata,
+    RoyaltyArgs,
+    SplitBetweenUnparsed,
+    TokenMetadata,
+};
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[cfg_attr(feature = "wasm", derive(BorshDeserialize, BorshSerialize))]
 pub struct StoreInitArgs {
     pub metadata: NFTContractMetadata,
     pub owner_id: AccountId,
+    #[serde(flatten)]
+    pub bootstrap: StoreBootstrapArgs,
+}
+
+/// Optional arguments to set up a `Store` within the transaction that creates
+/// it. Omitted fields keep the defaults of `Store::new`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "wasm", derive(BorshDeserialize, BorshSerialize))]
+pub struct StoreBootstrapArgs {
+    /// Accounts that may mint on the `Store` in addition to the owner.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minters: Option<Vec<AccountId>>,
+    /// Initial values of the store settings.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settings: Option<StoreSettings>,
+    /// Royalty to apply to tokens minted without explicit royalty.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_royalty: Option<RoyaltyArgs>,
+    /// Tokens to mint on behalf of the owner right after initialization.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub initial_mint: Option<StoreInitialMint>,
+}
+
+/// Policy settings of a `Store`. When used as arguments, omitted fields keep
+/// their current value.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "wasm", derive(BorshDeserialize, BorshSerialize))]
+pub struct StoreSettings {
+    /// Whether token owners may call `nft_move`.
+    pub allow_moves: Option<bool>,
+    /// Whether minting is currently paused.
+    pub minting_paused: Option<bool>,
+    /// Upper limit for royalty percentages (out of 10_000) on minted tokens.
+    pub max_royalty_percentage: Option<u32>,
+    /// Whether the owner of the `Factory` may upgrade the `Store` code.
+    pub allow_factory_upgrades: Option<bool>,
+    /// Whether the owner of the `Factory` may trigger emergency actions,
+    /// such as pausing minting, on the `Store`.
+    pub allow_factory_emergency_actions: Option<bool>,
+}
+
+/// Arguments for a `nft_batch_mint` that is executed during `Store`
+/// initialization, with the store owner as minter.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "wasm", derive(BorshDeserialize, BorshSerialize))]
+pub struct StoreInitialMint {
+    /// Receiver of the tokens. Defaults to the store owner.
+    pub owner_id: Option<AccountId>,
+    pub metadata: TokenMetadata,
+    pub num_to_mint: u64,
+    pub royalty_args: Option<RoyaltyArgs>,
+    pub split_owners: Option<SplitBetweenUnparsed>,
 }
@@ -0,0 +1,80 @@
+use near_sdk::borsh::{
+    self,
+    BorshDeserialize,
+    BorshSerialize,
+};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// Rules that the `Factory` applies to the names of new `Store`s, on top of
+/// them resulting in valid account IDs.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "wasm", derive(BorshDeserialize, BorshSerialize))]
+pub struct StoreNamePolicy {
+    /// Minimum number of characters in a store name.
+    pub min_length: u8,
+    /// Maximum number of characters in a store name.
+    pub max_length: u8,
+    /// Names matching any of these patterns are reserved. A pattern may start
+    /// and/or end with `*` to match any prefix and/or suffix, e.g.
+    /// `mintbase*` or `*near*`.
+    pub reserved_patterns: Vec<String>,
+    /// Names with at most this many characters are considered premium.
+    pub premium_length: u8,
+    /// Fee in yoctoNEAR that is charged on top of the regular deployment
+    /// cost for premium names.
+    pub premium_fee: U128,
+}
+
+impl Default for StoreNamePolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 1,
+            max_length: 64,
+            reserved_patterns: vec![],
+            premium_length: 0,
+            premium_fee: 0.into(),
+        }
+    }
+}
+
+impl StoreNamePolicy {
+    /// Whether `name` matches any of the reserved patterns.
+    pub fn is_reserved_pattern(
+        &self,
+        name: &str,
+    ) -> bool {
+        self.reserved_patterns
+            .iter()
+            .any(|pattern| matches_pattern(pattern, name))
+    }
+
+    /// The premium fee that is charged for `name`.
+    pub fn premium_fee_for(
+        &self,
+        name: &str,
+    ) -> u128 {
+        if name.len() <= self.premium_length as usize {
+            self.premium_fee.0
+        } else {
+            0
+        }
+    }
+}
+
+fn matches_pattern(
+    pattern: &str,
+    name: &str,
+) -> bool {
+    match (pattern.strip_prefix('*'), pattern.strip_suffix('*')) {
+        (Some(_), Some(_)) if pattern.len() >= 2 => name.contains(&pattern[1..pattern.len() - 1]),
+        (Some(suffix), None) => name.ends_with(suffix),
+        (None, Some(prefix)) => name.starts_with(prefix),
+        // a lone "*" matches everything
+        (Some(_), Some(_)) => true,
+        (None, None) => name == pattern,
+    }
+}
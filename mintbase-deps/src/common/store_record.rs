@@ -0,0 +1,34 @@
+use near_sdk::borsh::{
+    self,
+    BorshDeserialize,
+    BorshSerialize,
+};
+use near_sdk::serde::{
+    Deserialize,
+    Serialize,
+};
+use near_sdk::AccountId;
+
+use crate::common::{
+    NFTContractMetadata,
+    NearTime,
+};
+
+/// Registry entry of the `Factory` for a `Store` it has produced.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "wasm", derive(BorshDeserialize, BorshSerialize))]
+pub struct StoreRecord {
+    /// The full account ID of the `Store`.
+    pub store_id: AccountId,
+    /// The current owner of the `Store`, as last reported to the `Factory`.
+    pub owner_id: AccountId,
+    /// When the `Store` has been created. `None` for stores that have been
+    /// created before the registry existed.
+    pub created_at: Option<NearTime>,
+    /// Version of the store code that the `Store` is running.
+    pub code_version: u32,
+    /// The template the `Store` has been created from, if any.
+    pub template_id: Option<String>,
+    /// Contract metadata at the time of creation.
+    pub metadata: NFTContractMetadata,
+}
@@ -0,0 +1,30 @@
+use near_sdk::borsh::{
+    self,
+    BorshDeserialize,
+    BorshSerialize,
+};
+use near_sdk::serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// How a `Store` can be administered by its `Factory`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "wasm", derive(BorshDeserialize, BorshSerialize))]
+#[serde(rename_all = "snake_case")]
+pub enum StoreSecurityMode {
+    /// The `Store` account holds a full access key for the admin public key
+    /// of the `Factory`. All stores created before the introduction of
+    /// security modes use this mode.
+    AdminKey,
+    /// The `Store` account holds no access keys. Upgrades and emergency
+    /// actions are only possible via explicit calls from the `Factory`,
+    /// which the store owner may opt out of.
+    FactoryCalls,
+}
+
+impl Default for StoreSecurityMode {
+    fn default() -> Self {
+        Self::AdminKey
+    }
+}
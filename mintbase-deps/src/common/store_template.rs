@@ -0,0 +1,32 @@
+use near_sdk::borsh::{
+    self,
+    BorshDeserialize,
+    BorshSerialize,
+};
+use near_sdk::json_types::U128;
+use near_sdk::serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// A flavor of `Store` that the `Factory` may deploy, e.g. a basic store, a
+/// ticketing store or a store for soulbound tokens.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "wasm", derive(BorshDeserialize, BorshSerialize))]
+pub struct StoreTemplate {
+    /// Version of the store code as uploaded to the `Factory`. Version 0
+    /// refers to the code that has been compiled into the `Factory`.
+    pub code_version: u32,
+    /// Storage stake in yoctoNEAR that is transferred to each `Store` created
+    /// from this template.
+    pub store_cost: U128,
+    /// JSON schema describing the template-specific arguments that may be
+    /// passed to `create_store`. Informational only, the `Store` itself
+    /// validates its init args.
+    pub init_args_schema: Option<String>,
+    /// JSON object with default init args, e.g. to enable open minting.
+    /// Arguments passed to `create_store` take precedence.
+    pub default_init_args: Option<String>,
+    /// Disabled templates cannot be used to create new stores.
+    pub enabled: bool,
+}
@@ -0,0 +1,21 @@
+use near_sdk::borsh::{
+    self,
+    BorshDeserialize,
+    BorshSerialize,
+};
+use near_sdk::AccountId;
+
+/// Arguments that the `Factory` passes to `Store::upgrade`. These are
+/// serialized using borsh, as JSON-encoding the contract code would be
+/// prohibitively expensive.
+#[cfg_attr(feature = "wasm", derive(BorshDeserialize, BorshSerialize))]
+pub struct StoreUpgradeArgs {
+    /// The contract code to deploy on the `Store`.
+    pub code: Vec<u8>,
+    /// The version of the contract code as registered on the `Factory`.
+    pub version: u32,
+    /// The account that requested the upgrade on the `Factory`.
+    pub requested_by: AccountId,
+    /// Whether `requested_by` is the owner of the `Factory`.
+    pub by_factory_owner: bool,
+}
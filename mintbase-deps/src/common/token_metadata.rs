@@ -25,7 +25,7 @@ pub struct TokenMetadata {
     /// Required if `media` is included.
     pub media_hash: Option<Base64VecU8>,
     /// number of copies of this set of metadata in existence when token was minted.
-    pub copies: Option<u16>,
+    pub copies: Option<u64>,
     /// ISO 8601 datetime when token expires.
     pub expires_at: Option<String>,
     /// ISO 8601 datetime when token starts being valid.
@@ -65,7 +65,7 @@ impl TokenMetadata {
             description: args.description,
             media: args.media,
             media_hash: args.media_hash,
-            copies: (copies as u16).into(),
+            copies: Some(copies),
             expires_at: args.expires_at,
             starts_at: args.starts_at,
             extra: args.extra,
@@ -96,7 +96,7 @@ pub struct TokenMetadataCompliant {
     /// Required if `media` is included.
     pub media_hash: Option<Base64VecU8>,
     /// number of copies of this set of metadata in existence when token was minted.
-    pub copies: Option<u16>,
+    pub copies: Option<u64>,
     /// When token was issued or minted, Unix epoch in milliseconds
     pub issued_at: Option<String>,
     /// ISO 8601 datetime when token expires.
@@ -4,6 +4,8 @@ use near_sdk::borsh::{
     BorshDeserialize,
     BorshSerialize,
 };
+use near_sdk::json_types::U128;
+use near_sdk::serde::Serialize;
 use near_sdk::Balance;
 
 // #[cfg(feature = "all")]
@@ -40,9 +42,35 @@ pub mod gas {
     /// Gas requirements for creating a store.
     pub const CREATE_STORE: Gas = tgas(65 + 5);
 
+    /// Gas requirements for creating a store and minting the first tokens
+    /// during its initialization.
+    pub const CREATE_STORE_WITH_MINT: Gas = tgas(65 + 5 + 100);
+
     /// Gas requirements for
     pub const ON_CREATE_CALLBACK: Gas = tgas(10);
 
+    /// Gas requirements for deploying new code on a store and migrating its
+    /// state.
+    pub const UPGRADE_STORE: Gas = tgas(100);
+
+    /// Gas requirements for migrating the state of a store.
+    pub const MIGRATE_STORE: Gas = tgas(50);
+
+    /// Gas requirements for handling the callback of a store upgrade.
+    pub const ON_UPGRADE_CALLBACK: Gas = tgas(10);
+
+    /// Gas requirements for updating the store owner in the factory registry.
+    pub const UPDATE_STORE_OWNER: Gas = tgas(10);
+
+    /// Gas requirements for retiring a store in the factory registry.
+    pub const RETIRE_STORE: Gas = tgas(10);
+
+    /// Gas requirements for deleting a store after it has been retired.
+    pub const ON_RETIRE_STORE_CALLBACK: Gas = tgas(10);
+
+    /// Gas requirements for emergency actions triggered by the factory.
+    pub const STORE_EMERGENCY_ACTION: Gas = tgas(10);
+
     /// Gas requirements for
     pub const NFT_BATCH_APPROVE: Gas = tgas(100);
 
@@ -55,6 +83,28 @@ pub mod gas {
 
     /// Gas requirements for `nft_transfer_call`
     pub const NFT_ON_APPROVE: Gas = tgas(25);
+
+    /// Gas that batch methods reserve for their fixed costs, such as loading
+    /// and saving the contract state and emitting event logs.
+    pub const BATCH_BASE: Gas = tgas(5);
+
+    // The gas per token of the batch methods is an upper bound of the gas
+    // burnt per token, as measured by the `core::batch-gas-per-item` test for
+    // an owner who holds 1000 tokens. Tree operations get more expensive as
+    // trees grow, so this needs to be re-measured whenever the indexes
+    // touched by these methods change.
+
+    /// Gas for minting a single token in a batch.
+    pub const MINT_PER_TOKEN: Gas = tgas(2);
+
+    /// Gas for transferring a single token in a batch.
+    pub const TRANSFER_PER_TOKEN: Gas = tgas(3);
+
+    /// Gas for approving a single token in a batch.
+    pub const APPROVE_PER_TOKEN: Gas = tgas(2);
+
+    /// Gas for burning a single token in a batch.
+    pub const BURN_PER_TOKEN: Gas = tgas(3);
 }
 
 pub mod storage_bytes {
@@ -63,7 +113,10 @@ pub mod storage_bytes {
     pub const STORE: StorageUsage = 550_000;
 
     /// Storage bytes for a maximum size token without any metadata and without
-    /// any royalties.
+    /// any royalties. The Borsh encoding of a `Token` with 64 character owner
+    /// and minter IDs and no splits, loan or origin takes 167 bytes, its key
+    /// another 9 bytes, and the record overhead 40 bytes. The remainder is
+    /// cushion for the entries in the owner and minter indexes.
     pub const TOKEN: StorageUsage = 360;
 
     /// Storage bytes for some common components:
@@ -72,7 +125,24 @@ pub mod storage_bytes {
     /// - a single approval
     /// - an entry in the `tokens_per_account` map
     /// - an entry in the `composeables` map
+    ///
+    /// Each of these is dominated by one account ID, which takes up to 68
+    /// bytes in Borsh, leaving 12 bytes for the accompanying value, such as
+    /// an approval ID or a split share.
     pub const COMMON: StorageUsage = 80;
+
+    /// Storage bytes that Near charges for each key-value record, on top of
+    /// the bytes of key and value.
+    pub const RECORD: StorageUsage = 40;
+
+    /// Storage bytes of a `TreeMap<u64, _>` node without children: its ID,
+    /// key and height, plus two empty child options. Each child adds 8 bytes.
+    pub const TREE_NODE: StorageUsage = 26;
+
+    /// Storage bytes for a single token listing on the market. A listing
+    /// holds a token key and up to two account IDs, so this uses the same
+    /// bound as `TOKEN`.
+    pub const LIST: StorageUsage = 360;
 }
 
 pub mod storage_stake {
@@ -119,6 +189,10 @@ pub const MAX_LEN_PAYOUT: u32 = 50;
 /// Minimum storage stake required to allow updates
 pub const MINIMUM_FREE_STORAGE_STAKE: near_sdk::Balance = 50 * YOCTO_PER_BYTE;
 
+/// Upper limit for the timelock on administrative store changes, 30 days in
+/// nanoseconds. Prevents owners from locking their store settings forever.
+pub const MAX_ADMIN_DELAY: u64 = 30 * 24 * 3600 * 10u64.pow(9);
+
 //?
 
 // /// The amount of Storage in bytes consumed by a maximal sized Token with NO
@@ -154,13 +228,30 @@ pub struct StorageCosts {
 }
 
 impl StorageCosts {
+    /// Derive all storage costs from the given price per byte.
     pub fn new(storage_price_per_byte: u128) -> Self {
         Self {
             storage_price_per_byte,
-            // common: storage_price_per_byte * 80_u64 as u128,
-            common: storage_stake::COMMON,
-            // token: storage_price_per_byte * 360_u64 as u128,
-            token: storage_stake::TOKEN,
+            common: storage_price_per_byte * storage_bytes::COMMON as u128,
+            token: storage_price_per_byte * storage_bytes::TOKEN as u128,
+        }
+    }
+}
+
+/// JSON representation of `StorageCosts`, as returned by views.
+#[derive(Serialize)]
+pub struct StorageCostsJson {
+    pub storage_price_per_byte: U128,
+    pub common: U128,
+    pub token: U128,
+}
+
+impl From<&StorageCosts> for StorageCostsJson {
+    fn from(costs: &StorageCosts) -> Self {
+        Self {
+            storage_price_per_byte: costs.storage_price_per_byte.into(),
+            common: costs.common.into(),
+            token: costs.token.into(),
         }
     }
 }
@@ -175,11 +266,11 @@ pub struct StorageCostsMarket {
 }
 
 impl StorageCostsMarket {
+    /// Derive all storage costs from the given price per byte.
     pub fn new(storage_price_per_byte: u128) -> Self {
         Self {
             storage_price_per_byte,
-            // list: storage_price_per_byte * LIST_STORAGE as u128,
-            list: storage_stake::TOKEN,
+            list: storage_price_per_byte * storage_bytes::LIST as u128,
         }
     }
 }
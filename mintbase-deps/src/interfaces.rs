@@ -121,6 +121,19 @@ mod store_interfaces {
             msg: String,
         ) -> Promise;
     }
+
+    /// Methods of the `Factory` that a `Store` may call.
+    #[ext_contract(ext_factory)]
+    pub trait StoreFactory {
+        /// Update the owner of the calling `Store` in the factory registry.
+        fn update_store_owner(
+            &mut self,
+            owner_id: AccountId,
+        );
+
+        /// Mark the calling `Store` as retired in the factory registry.
+        fn retire_store(&mut self);
+    }
 }
 
 #[cfg(feature = "factory-wasm")]
@@ -136,7 +149,10 @@ mod factory_interfaces {
         ext_contract,
     };
 
-    use crate::common::NFTContractMetadata;
+    use crate::common::{
+        NFTContractMetadata,
+        StoreSecurityMode,
+    };
 
     #[ext_contract(factory_self)]
     pub trait OnCreateCallback {
@@ -147,6 +163,20 @@ mod factory_interfaces {
             owner_id: AccountId,
             store_account_id: AccountId,
             attached_deposit: U128,
+            store_version: u32,
+            store_cost: U128,
+            forwarded_deposit: U128,
+            fee: U128,
+            template_id: Option<String>,
+            referrer_id: Option<AccountId>,
+            security_mode: StoreSecurityMode,
+        );
+
+        fn on_upgrade(
+            &mut self,
+            store_id: AccountId,
+            version: u32,
+            requested_by: AccountId,
         );
     }
 }
@@ -3,6 +3,9 @@ pub mod common;
 pub mod constants;
 pub mod interfaces;
 pub mod logging;
+/// Previous state layouts of the smart contracts and the migrations between
+/// them.
+pub mod migrations;
 pub mod token;
 pub mod utils;
 
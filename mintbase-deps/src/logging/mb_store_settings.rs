@@ -1,4 +1,8 @@
 use near_events::near_event_data;
+use near_sdk::json_types::{
+    U128,
+    U64,
+};
 #[cfg(feature = "de")]
 use near_sdk::serde::Deserialize;
 #[cfg(feature = "ser")]
@@ -8,13 +12,79 @@ use near_sdk::{
     AccountId,
 };
 
-use crate::common::NFTContractMetadata;
+use crate::common::{
+    NFTContractMetadata,
+    PendingStoreChange,
+    StoreChange,
+};
 
-#[near_event_data(standard = "mb_store", version = "0.1.0", event = "deploy")]
+#[near_event_data(standard = "mb_store", version = "0.2.0", event = "deploy")]
 pub struct MbStoreDeployData {
     pub contract_metadata: NFTContractMetadata,
     pub owner_id: String,
     pub store_id: String,
+    pub referrer_id: Option<String>,
+    pub referral_amount: Option<U128>,
+}
+
+#[near_event_data(standard = "mb_store", version = "0.1.0", event = "upgrade")]
+pub struct MbStoreUpgradeData {
+    pub store_id: String,
+    pub version: u32,
+    pub requested_by: String,
+}
+
+pub fn log_store_upgrade(
+    store_id: &AccountId,
+    version: u32,
+    requested_by: &AccountId,
+) {
+    env::log_str(
+        &MbStoreUpgradeData {
+            store_id: store_id.to_string(),
+            version,
+            requested_by: requested_by.to_string(),
+        }
+        .serialize_event(),
+    );
+}
+
+#[near_event_data(standard = "mb_store", version = "0.1.0", event = "decommission")]
+pub struct MbStoreDecommissionData {
+    pub store_id: String,
+    pub beneficiary_id: String,
+}
+
+pub fn log_store_decommission(
+    store_id: &AccountId,
+    beneficiary_id: &AccountId,
+) {
+    env::log_str(
+        &MbStoreDecommissionData {
+            store_id: store_id.to_string(),
+            beneficiary_id: beneficiary_id.to_string(),
+        }
+        .serialize_event(),
+    );
+}
+
+#[near_event_data(standard = "mb_store", version = "0.1.0", event = "emergency_pause")]
+pub struct MbStoreEmergencyPauseData {
+    pub store_id: String,
+    pub factory_id: String,
+}
+
+pub fn log_emergency_pause_minting(
+    store_id: &AccountId,
+    factory_id: &AccountId,
+) {
+    env::log_str(
+        &MbStoreEmergencyPauseData {
+            store_id: store_id.to_string(),
+            factory_id: factory_id.to_string(),
+        }
+        .serialize_event(),
+    );
 }
 
 #[near_event_data(standard = "mb_store", version = "0.1.0", event = "change_setting")]
@@ -91,3 +161,67 @@ pub fn log_set_base_uri(base_uri: &str) {
         .serialize_event(),
     );
 }
+
+// -------------------------------- timelock -------------------------------- //
+
+#[near_event_data(standard = "mb_store", version = "0.1.0", event = "schedule_change")]
+pub struct MbStoreScheduleChangeData {
+    pub change_id: U64,
+    pub change: StoreChange,
+    pub executable_at: U64,
+}
+
+#[near_event_data(standard = "mb_store", version = "0.1.0", event = "execute_change")]
+pub struct MbStoreExecuteChangeData {
+    pub change_id: U64,
+    pub change: StoreChange,
+}
+
+#[near_event_data(standard = "mb_store", version = "0.1.0", event = "cancel_change")]
+pub struct MbStoreCancelChangeData {
+    pub change_id: U64,
+}
+
+#[near_event_data(standard = "mb_store", version = "0.1.0", event = "set_admin_delay")]
+pub struct MbStoreSetAdminDelayData {
+    pub admin_delay: U64,
+}
+
+pub fn log_set_admin_delay(admin_delay: u64) {
+    env::log_str(
+        &MbStoreSetAdminDelayData {
+            admin_delay: admin_delay.into(),
+        }
+        .serialize_event(),
+    );
+}
+
+pub fn log_schedule_store_change(pending: &PendingStoreChange) {
+    env::log_str(
+        &MbStoreScheduleChangeData {
+            change_id: pending.id,
+            change: pending.change.clone(),
+            executable_at: pending.executable_at.0.into(),
+        }
+        .serialize_event(),
+    );
+}
+
+pub fn log_execute_store_change(pending: &PendingStoreChange) {
+    env::log_str(
+        &MbStoreExecuteChangeData {
+            change_id: pending.id,
+            change: pending.change.clone(),
+        }
+        .serialize_event(),
+    );
+}
+
+pub fn log_cancel_store_change(change_id: u64) {
+    env::log_str(
+        &MbStoreCancelChangeData {
+            change_id: change_id.into(),
+        }
+        .serialize_event(),
+    );
+}
@@ -9,6 +9,8 @@ use near_sdk::{
     AccountId,
 };
 
+use crate::common::ApprovalScope;
+
 #[cfg_attr(feature = "ser", derive(Serialize))]
 #[cfg_attr(feature = "de", derive(Deserialize))]
 #[cfg_attr(any(feature = "ser", feature = "de"), serde(crate = "near_sdk::serde"))]
@@ -16,6 +18,11 @@ pub struct NftApproveLog {
     pub token_id: U64,
     pub approval_id: u64,
     pub account_id: String,
+    #[cfg_attr(
+        any(feature = "ser", feature = "de"),
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub scope: Option<ApprovalScope>,
 }
 
 #[near_event_data(standard = "mb_store", version = "0.1.0", event = "nft_approve")]
@@ -25,11 +32,13 @@ pub fn log_approve(
     token_id: u64,
     approval_id: u64,
     account_id: &AccountId,
+    scope: &Option<ApprovalScope>,
 ) {
     let data = NftApproveData(vec![NftApproveLog {
         token_id: token_id.into(),
         approval_id,
         account_id: account_id.to_string(),
+        scope: scope.clone(),
     }]);
     env::log_str(&data.serialize_event());
 }
@@ -38,6 +47,7 @@ pub fn log_batch_approve(
     tokens: &[U64],
     approvals: &[U64],
     account_id: &AccountId,
+    scope: &Option<ApprovalScope>,
 ) {
     let data = NftApproveData(
         approvals
@@ -47,6 +57,7 @@ pub fn log_batch_approve(
                 token_id: *token_id,
                 approval_id: approval_id.0,
                 account_id: account_id.to_string(),
+                scope: scope.clone(),
             })
             .collect::<Vec<_>>(),
     );
@@ -85,3 +96,41 @@ pub fn log_revoke_all(token_id: u64) {
         .serialize_event(),
     );
 }
+
+#[near_event_data(standard = "mb_store", version = "0.1.0", event = "nft_approve_for_all")]
+pub struct NftApproveForAllData {
+    pub owner_id: String,
+    pub account_id: String,
+}
+
+pub fn log_approve_for_all(
+    owner_id: &AccountId,
+    account_id: &AccountId,
+) {
+    env::log_str(
+        &NftApproveForAllData {
+            owner_id: owner_id.to_string(),
+            account_id: account_id.to_string(),
+        }
+        .serialize_event(),
+    );
+}
+
+#[near_event_data(standard = "mb_store", version = "0.1.0", event = "nft_revoke_for_all")]
+pub struct NftRevokeForAllData {
+    pub owner_id: String,
+    pub account_id: String,
+}
+
+pub fn log_revoke_for_all(
+    owner_id: &AccountId,
+    account_id: &AccountId,
+) {
+    env::log_str(
+        &NftRevokeForAllData {
+            owner_id: owner_id.to_string(),
+            account_id: account_id.to_string(),
+        }
+        .serialize_event(),
+    );
+}
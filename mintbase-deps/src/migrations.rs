@@ -0,0 +1,87 @@
+#[cfg(feature = "store-wasm")]
+use near_sdk::borsh::{
+    BorshDeserialize,
+    BorshSerialize,
+};
+use near_sdk::env;
+
+#[cfg(feature = "store-wasm")]
+use crate::common::MigrationProgress;
+
+#[cfg(feature = "factory-wasm")]
+mod factory_v1;
+#[cfg(feature = "factory-wasm")]
+pub use factory_v1::*;
+
+#[cfg(feature = "store-wasm")]
+mod store_v1;
+#[cfg(all(test, feature = "store-wasm"))]
+mod tests;
+#[cfg(feature = "store-wasm")]
+mod token_metadata_v1;
+#[cfg(feature = "store-wasm")]
+mod token_v1;
+#[cfg(feature = "store-wasm")]
+pub use store_v1::*;
+#[cfg(feature = "store-wasm")]
+pub use token_metadata_v1::*;
+#[cfg(feature = "store-wasm")]
+pub use token_v1::*;
+
+/// Storage key under which the version of the state layout is kept. It is
+/// kept separate from the contract state, so that it can be read without
+/// knowing the layout.
+pub const STATE_VERSION_KEY: &[u8] = b"state_version";
+
+/// Read the version of the state layout. Contracts that have been deployed
+/// before the introduction of versioning have not stored any version, and
+/// are thus considered to be at version 1.
+pub fn read_state_version() -> u32 {
+    env::storage_read(STATE_VERSION_KEY)
+        .map(|bytes| {
+            let mut buf = [0u8; 4];
+            buf.copy_from_slice(&bytes);
+            u32::from_le_bytes(buf)
+        })
+        .unwrap_or(1)
+}
+
+/// Store the version of the state layout.
+pub fn write_state_version(version: u32) {
+    env::storage_write(STATE_VERSION_KEY, &version.to_le_bytes());
+}
+
+/// Storage key under which the progress of a running backfill is kept. Like
+/// the state version, it is kept separate from the contract state, so that
+/// a backfill does not change the layout.
+#[cfg(feature = "store-wasm")]
+pub const MIGRATION_PROGRESS_KEY: &[u8] = b"migration";
+
+/// Read the progress of the running backfill, if any.
+#[cfg(feature = "store-wasm")]
+pub fn read_migration_progress() -> Option<MigrationProgress> {
+    env::storage_read(MIGRATION_PROGRESS_KEY).map(|bytes| {
+        MigrationProgress::try_from_slice(&bytes).expect("Cannot deserialize migration progress")
+    })
+}
+
+/// Store the progress of the running backfill, or remove it once the
+/// backfill is complete.
+#[cfg(feature = "store-wasm")]
+pub fn write_migration_progress(progress: Option<&MigrationProgress>) {
+    match progress {
+        Some(progress) => env::storage_write(
+            MIGRATION_PROGRESS_KEY,
+            &progress
+                .try_to_vec()
+                .expect("Cannot serialize migration progress"),
+        ),
+        None => env::storage_remove(MIGRATION_PROGRESS_KEY),
+    };
+}
+
+/// Whether a backfill is running, without reading its progress.
+#[cfg(feature = "store-wasm")]
+pub fn is_migrating() -> bool {
+    env::storage_has_key(MIGRATION_PROGRESS_KEY)
+}
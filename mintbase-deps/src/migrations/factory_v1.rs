@@ -0,0 +1,33 @@
+use near_sdk::borsh::{
+    self,
+    BorshDeserialize,
+    BorshSerialize,
+};
+use near_sdk::collections::LookupSet;
+use near_sdk::{
+    env,
+    AccountId,
+    Balance,
+    PublicKey,
+};
+
+/// State layout of the `Factory` before the introduction of versioning and
+/// uploadable store code.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct MintbaseStoreFactoryV1 {
+    pub stores: LookupSet<String>,
+    pub mintbase_fee: Balance,
+    pub owner_id: AccountId,
+    pub storage_price_per_byte: u128,
+    pub store_cost: u128,
+    pub admin_public_key: PublicKey,
+}
+
+impl MintbaseStoreFactoryV1 {
+    /// Read the state as version 1. This is the oldest known layout, so there
+    /// is nothing to migrate from.
+    pub fn read(version: u32) -> Self {
+        crate::near_assert_eq!(version, 1, "Unknown factory state version: {}", version);
+        env::state_read().expect("ohno ohno state")
+    }
+}
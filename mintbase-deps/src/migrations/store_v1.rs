@@ -0,0 +1,51 @@
+use near_sdk::borsh::{
+    self,
+    BorshDeserialize,
+    BorshSerialize,
+};
+use near_sdk::collections::{
+    LookupMap,
+    UnorderedSet,
+};
+use near_sdk::{
+    env,
+    AccountId,
+};
+
+use super::{
+    TokenMetadataV1,
+    TokenV1,
+};
+use crate::common::{
+    NFTContractMetadata,
+    Royalty,
+};
+use crate::constants::StorageCosts;
+
+/// State layout of the `Store` before the introduction of versioning and the
+/// timelock for administrative changes.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct MintbaseStoreV1 {
+    pub minters: UnorderedSet<AccountId>,
+    pub metadata: NFTContractMetadata,
+    pub token_metadata: LookupMap<u64, (u16, TokenMetadataV1)>,
+    pub token_royalty: LookupMap<u64, (u16, Royalty)>,
+    pub tokens: LookupMap<u64, TokenV1>,
+    pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<u64>>,
+    pub composeables: LookupMap<String, UnorderedSet<String>>,
+    pub tokens_minted: u64,
+    pub tokens_burned: u64,
+    pub num_approved: u64,
+    pub owner_id: AccountId,
+    pub storage_costs: StorageCosts,
+    pub allow_moves: bool,
+}
+
+impl MintbaseStoreV1 {
+    /// Read the state as version 1. This is the oldest known layout, so there
+    /// is nothing to migrate from.
+    pub fn read(version: u32) -> Self {
+        crate::near_assert_eq!(version, 1, "Unknown store state version: {}", version);
+        env::state_read().expect("ohno ohno state")
+    }
+}
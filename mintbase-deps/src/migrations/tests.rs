@@ -0,0 +1,58 @@
+//! The baseline layout of the `Store` state has a Borsh fixture in
+//! `fixtures/`, encoded by hand rather than with the derived Borsh
+//! implementations. The fixture holds a store of Alice with three minted
+//! tokens, one of them burned, two approvals and a single minter.
+//! Collections are empty, with their field names as storage prefixes. The
+//! migration of a fully populated baseline store is tested in the store
+//! crate.
+use near_sdk::test_utils::VMContextBuilder;
+use near_sdk::{
+    env,
+    testing_env,
+};
+
+use super::MintbaseStoreV1;
+
+/// Storage key of the contract state.
+const STATE_KEY: &[u8] = b"STATE";
+
+/// Decode a fixture, ignoring line breaks.
+fn decode_hex(hex: &str) -> Vec<u8> {
+    let digits: Vec<u8> = hex
+        .bytes()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .map(|byte| (byte as char).to_digit(16).unwrap() as u8)
+        .collect();
+    digits
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair[1])
+        .collect()
+}
+
+/// Store the fixture of the baseline layout as contract state, then read it.
+#[test]
+fn reads_store_v1() {
+    testing_env!(VMContextBuilder::new().build());
+    env::storage_write(
+        STATE_KEY,
+        &decode_hex(include_str!("fixtures/store_v1.hex")),
+    );
+
+    let store = MintbaseStoreV1::read(1);
+    assert_eq!(store.metadata.spec, "nft-1.0.0");
+    assert_eq!(store.metadata.symbol, "ALICE");
+    assert_eq!(store.owner_id.as_str(), "alice.near");
+    assert_eq!(store.minters.len(), 1);
+    assert_eq!(store.tokens_minted, 3);
+    assert_eq!(store.tokens_burned, 1);
+    assert_eq!(store.num_approved, 2);
+    assert!(store.allow_moves);
+    assert_eq!(
+        store.storage_costs.storage_price_per_byte,
+        10_000_000_000_000_000_000
+    );
+    assert_eq!(
+        store.storage_costs.token,
+        360 * store.storage_costs.storage_price_per_byte
+    );
+}
@@ -0,0 +1,41 @@
+use near_sdk::borsh::{
+    self,
+    BorshDeserialize,
+    BorshSerialize,
+};
+use near_sdk::json_types::Base64VecU8;
+
+use crate::common::TokenMetadata;
+
+/// Layout of the `TokenMetadata` records before the number of copies has been
+/// widened to `u64`.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct TokenMetadataV1 {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub media: Option<String>,
+    pub media_hash: Option<Base64VecU8>,
+    pub copies: Option<u16>,
+    pub expires_at: Option<String>,
+    pub starts_at: Option<String>,
+    pub extra: Option<String>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<Base64VecU8>,
+}
+
+impl From<TokenMetadataV1> for TokenMetadata {
+    fn from(old: TokenMetadataV1) -> Self {
+        Self {
+            title: old.title,
+            description: old.description,
+            media: old.media,
+            media_hash: old.media_hash,
+            copies: old.copies.map(u64::from),
+            expires_at: old.expires_at,
+            starts_at: old.starts_at,
+            extra: old.extra,
+            reference: old.reference,
+            reference_hash: old.reference_hash,
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+
+use near_sdk::borsh::{
+    self,
+    BorshDeserialize,
+    BorshSerialize,
+};
+use near_sdk::AccountId;
+
+use crate::common::{
+    SplitOwners,
+    TokenKey,
+};
+use crate::token::{
+    ComposeableStats,
+    Loan,
+    Owner,
+    Token,
+};
+
+/// Layout of the `Token` records before approvals have been moved into their
+/// own collection.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct TokenV1 {
+    pub id: u64,
+    pub owner_id: Owner,
+    pub approvals: HashMap<AccountId, u64>,
+    pub metadata_id: u64,
+    pub royalty_id: Option<u64>,
+    pub split_owners: Option<SplitOwners>,
+    pub minter: AccountId,
+    pub loan: Option<Loan>,
+    pub composeable_stats: ComposeableStats,
+    pub origin_key: Option<TokenKey>,
+}
+
+impl TokenV1 {
+    /// Split the record into the current `Token` layout and its approvals.
+    pub fn into_token_and_approvals(self) -> (Token, HashMap<AccountId, u64>) {
+        let token = Token {
+            id: self.id,
+            owner_id: self.owner_id,
+            metadata_id: self.metadata_id,
+            royalty_id: self.royalty_id,
+            split_owners: self.split_owners,
+            minter: self.minter,
+            loan: self.loan,
+            composeable_stats: self.composeable_stats,
+            origin_key: self.origin_key,
+        };
+        (token, self.approvals)
+    }
+}
@@ -12,6 +12,7 @@ use near_sdk::serde::{
 use near_sdk::AccountId;
 
 use crate::common::{
+    ApprovalScope,
     Royalty,
     SplitOwners,
     TokenKey,
@@ -27,6 +28,10 @@ pub use owner::Owner;
 
 /// Supports NEP-171, 177, 178, 181. Ref:
 /// https://github.com/near/NEPs/blob/master/specs/Standards/NonFungibleToken/Core.md
+///
+/// Approvals are not part of the token record, but are kept in the `Store`
+/// `token_approvals` field, so that reading and writing tokens does not
+/// require (de)serializing them.
 #[derive(Clone)]
 #[cfg_attr(feature = "wasm", derive(BorshDeserialize, BorshSerialize))]
 #[derive(Deserialize, Serialize)]
@@ -36,10 +41,6 @@ pub struct Token {
     pub id: u64,
     /// The current owner of this token. Either an account_id or a token_id (if composed).
     pub owner_id: Owner,
-    /// Ref:
-    /// https://github.com/near/NEPs/blob/master/specs/Standards/NonFungibleToken/ApprovalManagement.md
-    /// Set of accounts that may transfer this token, other than the owner.
-    pub approvals: HashMap<AccountId, u64>,
     /// The metadata content for this token is stored in the Contract
     /// `token_metadata` field, to avoid duplication of metadata across tokens.
     /// Use metadata_id to lookup the metadata. `Metadata`s is permanently set
@@ -83,7 +84,6 @@ impl Token {
             metadata_id,
             royalty_id,
             split_owners,
-            approvals: HashMap::new(),
             minter,
             loan: None,
             composeable_stats: ComposeableStats::new(),
@@ -128,6 +128,9 @@ pub struct TokenCompliant {
     /// https://github.com/near/NEPs/blob/master/specs/Standards/NonFungibleToken/ApprovalManagement.md
     /// Set of accounts that may transfer this token, other than the owner.
     pub approved_account_ids: HashMap<AccountId, u64>,
+    /// Constraints on the approvals in `approved_account_ids`, for those
+    /// accounts whose approval has been given with an `ApprovalScope`.
+    pub approval_scopes: HashMap<AccountId, ApprovalScope>,
     /// The metadata content for this token is stored in the Contract
     /// `token_metadata` field, to avoid duplication of metadata across tokens.
     /// Use metadata_id to lookup the metadata. `Metadata`s is permanently set
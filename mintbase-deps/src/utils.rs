@@ -1,6 +1,22 @@
+#[cfg(feature = "store-wasm")]
+use std::convert::TryInto;
+
+#[cfg(feature = "store-wasm")]
+use near_sdk::borsh::BorshSerialize;
 #[cfg(feature = "market-wasm")]
 use near_sdk::Balance;
 use near_sdk::Gas;
+#[cfg(feature = "store-wasm")]
+use near_sdk::{
+    env,
+    CryptoHash,
+};
+
+#[cfg(feature = "store-wasm")]
+use crate::common::{
+    Royalty,
+    TokenMetadata,
+};
 
 /// Split a &str on the first colon
 pub fn split_colon(string: &str) -> (&str, &str) {
@@ -19,6 +35,27 @@ pub const fn ntoy(near_amount: Balance) -> Balance {
     near_amount * 10u128.pow(24)
 }
 
+/// Content hash of `metadata`, under which the store indexes it in
+/// `metadata_ids_by_hash`. The number of copies is ignored, as it changes
+/// with each mint of the same metadata.
+#[cfg(feature = "store-wasm")]
+pub fn metadata_hash(metadata: &TokenMetadata) -> CryptoHash {
+    let mut metadata = metadata.clone();
+    metadata.copies = None;
+    env::sha256(&metadata.try_to_vec().unwrap())
+        .try_into()
+        .unwrap()
+}
+
+/// Content hash of `royalty`, under which the store indexes it in
+/// `royalty_ids_by_hash`.
+#[cfg(feature = "store-wasm")]
+pub fn royalty_hash(royalty: &Royalty) -> CryptoHash {
+    env::sha256(&royalty.try_to_vec().unwrap())
+        .try_into()
+        .unwrap()
+}
+
 // // TODO: unused, deprecated?
 // pub fn to_yocto(value: &str) -> u128 {
 //     let vals: Vec<_> = value.split('.').collect();
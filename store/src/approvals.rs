@@ -1,10 +1,18 @@
+use std::collections::{
+    HashMap,
+    HashSet,
+};
+
+use mintbase_deps::common::ApprovalScope;
 use mintbase_deps::constants::gas;
 use mintbase_deps::interfaces::ext_on_approve;
 use mintbase_deps::logging::{
     log_approve,
+    log_approve_for_all,
     log_batch_approve,
     log_revoke,
     log_revoke_all,
+    log_revoke_for_all,
 };
 use mintbase_deps::near_sdk::json_types::U64;
 use mintbase_deps::near_sdk::{
@@ -12,14 +20,21 @@ use mintbase_deps::near_sdk::{
     env,
     near_bindgen,
     AccountId,
+    Balance,
+    Gas,
     Promise,
+    StorageUsage,
+};
+use mintbase_deps::token::{
+    Owner,
+    Token,
 };
-use mintbase_deps::token::Token;
 use mintbase_deps::{
     assert_storage_deposit,
     assert_token_owned_by_predecessor,
     assert_token_unloaned,
     assert_yocto_deposit,
+    near_assert,
 };
 
 use crate::*;
@@ -28,21 +43,26 @@ use crate::*;
 #[near_bindgen]
 impl MintbaseStore {
     // -------------------------- change methods ---------------------------
+    /// Approve `account_id` to transfer the token with `token_id`. The
+    /// approval may be constrained by a `scope`, which requires storage for
+    /// a second record. The attached deposit needs to cover the storage that
+    /// the approval takes, and any excess is refunded.
     #[payable]
     pub fn nft_approve(
         &mut self,
         token_id: U64,
         account_id: AccountId,
         msg: Option<String>,
+        scope: Option<ApprovalScope>,
     ) -> Option<Promise> {
-        // Note: This method only guarantees that the store-storage is covered.
-        // The market may still reject.
-        assert_storage_deposit!(self.storage_costs.common);
-        // assert!(env::attached_deposit() > self.storage_costs.common);
         let token_idu64 = token_id.into();
+        let usage_before = env::storage_usage();
         // validates owner and loaned
-        let approval_id = self.approve_internal(token_idu64, &account_id);
-        log_approve(token_idu64, approval_id, &account_id);
+        let approval_id = self.approve_internal(token_idu64, &account_id, &scope);
+        // Note: This method only guarantees that the store-storage is covered.
+        // The market may still reject.
+        refund_deposit(self.charge_storage_since(usage_before));
+        log_approve(token_idu64, approval_id, &account_id, &scope);
 
         if let Some(msg) = msg {
             ext_on_approve::nft_on_approve(
@@ -66,16 +86,20 @@ impl MintbaseStore {
         token_id: U64,
         account_id: AccountId,
     ) {
+        self.assert_not_migrating();
         let token_idu64 = token_id.into();
-        let mut token = self.nft_token_internal(token_idu64);
+        let token = self.nft_token_internal(token_idu64);
         // token.assert_unloaned();
         // token.assert_owned_by_predecessor();
         assert_token_unloaned!(token);
         assert_token_owned_by_predecessor!(token);
         assert_yocto_deposit!();
 
-        if token.approvals.remove(&account_id).is_some() {
-            self.tokens.insert(&token_idu64, &token);
+        let mut approvals = self.token_approvals_internal(token_idu64);
+        if let Some(approval_id) = approvals.remove(&account_id) {
+            self.set_token_approvals(token_idu64, &approvals);
+            self.approval_scopes.remove(&approval_id);
+            self.unindex_approval(&account_id, token_idu64);
             log_revoke(token_idu64, &account_id);
         }
         // TODO: refund storage deposit
@@ -86,17 +110,16 @@ impl MintbaseStore {
         &mut self,
         token_id: U64,
     ) {
+        self.assert_not_migrating();
         let token_idu64 = token_id.into();
-        let mut token = self.nft_token_internal(token_idu64);
+        let token = self.nft_token_internal(token_idu64);
         // token.assert_unloaned();
         // token.assert_owned_by_predecessor();
         assert_token_unloaned!(token);
         assert_token_owned_by_predecessor!(token);
         assert_yocto_deposit!();
 
-        if !token.approvals.is_empty() {
-            token.approvals.clear();
-            self.tokens.insert(&token_idu64, &token);
+        if self.clear_token_approvals(token_idu64) {
             log_revoke_all(token_idu64);
         }
         // TODO: refund storage deposit
@@ -121,31 +144,44 @@ impl MintbaseStore {
 #[near_bindgen]
 impl MintbaseStore {
     // -------------------------- change methods ---------------------------
+    /// Approve `account_id` on each token in `token_ids`, with the same
+    /// optional `scope` for each approval. The prepaid gas must cover
+    /// `gas::APPROVE_PER_TOKEN` per token, on top of `gas::BATCH_BASE` and,
+    /// if `msg` is given, the gas for `nft_on_batch_approve`. Otherwise, the
+    /// method fails before approving.
+    ///
+    /// The attached deposit needs to cover the storage that the approvals
+    /// take. Any excess is forwarded to `nft_on_batch_approve` if `msg` is
+    /// given, and refunded otherwise.
     #[payable]
     pub fn nft_batch_approve(
         &mut self,
         token_ids: Vec<U64>,
         account_id: AccountId,
         msg: Option<String>,
+        scope: Option<ApprovalScope>,
     ) -> Option<Promise> {
-        let tlen = token_ids.len() as u128;
+        let tlen = token_ids.len() as u64;
         assert!(tlen > 0);
-        assert!(tlen <= 70);
-        let storage_stake = self.storage_costs.common * tlen;
-        // Note: This method only guarantees that the store-storage is covered.
-        // The financial contract may still reject.
-        assert_storage_deposit!(storage_stake);
-        // assert!(
-        //     env::attached_deposit() > store_approval_storage,
-        //     "deposit less than: {}",
-        //     store_approval_storage
-        // );
+        let reserved = if msg.is_some() {
+            gas::NFT_BATCH_APPROVE
+        } else {
+            Gas(0)
+        };
+        assert_batch_gas(tlen, gas::APPROVE_PER_TOKEN, reserved);
+        let usage_before = env::storage_usage();
         let approval_ids: Vec<U64> = token_ids
             .iter()
             // validates owner and loaned
-            .map(|&token_id| self.approve_internal(token_id.into(), &account_id).into())
+            .map(|&token_id| {
+                self.approve_internal(token_id.into(), &account_id, &scope)
+                    .into()
+            })
             .collect();
-        log_batch_approve(&token_ids, &approval_ids, &account_id);
+        // Note: This method only guarantees that the store-storage is covered.
+        // The financial contract may still reject.
+        let excess_deposit = self.charge_storage_since(usage_before);
+        log_batch_approve(&token_ids, &approval_ids, &account_id, &scope);
 
         if let Some(msg) = msg {
             ext_on_approve::nft_on_batch_approve(
@@ -154,19 +190,75 @@ impl MintbaseStore {
                 env::predecessor_account_id(),
                 msg,
                 account_id,
-                env::attached_deposit() - storage_stake,
+                excess_deposit,
                 gas::NFT_BATCH_APPROVE,
             )
             .into()
         } else {
+            refund_deposit(excess_deposit);
             None
         }
     }
 
+    /// Approve `account_id` as operator for all tokens of the predecessor on
+    /// this `Store`, including tokens they receive later. Operators may
+    /// transfer these tokens without an approval ID, until the operator
+    /// approval is revoked via `nft_revoke_for_all`.
+    #[payable]
+    pub fn nft_approve_for_all(
+        &mut self,
+        account_id: AccountId,
+    ) {
+        assert_storage_deposit!(self.storage_costs.common);
+        let owner_id = env::predecessor_account_id();
+        let mut operators = self.operators_internal(&owner_id);
+        if operators.insert(account_id.clone()) {
+            self.operator_approvals.insert(&owner_id, &operators);
+            log_approve_for_all(&owner_id, &account_id);
+        }
+    }
+
+    /// Revoke the operator approval of `account_id` for the tokens of the
+    /// predecessor. Approvals on single tokens are not affected.
+    #[payable]
+    pub fn nft_revoke_for_all(
+        &mut self,
+        account_id: AccountId,
+    ) {
+        assert_yocto_deposit!();
+        let owner_id = env::predecessor_account_id();
+        let mut operators = self.operators_internal(&owner_id);
+        if operators.remove(&account_id) {
+            if operators.is_empty() {
+                self.operator_approvals.remove(&owner_id);
+            } else {
+                self.operator_approvals.insert(&owner_id, &operators);
+            }
+            log_revoke_for_all(&owner_id, &account_id);
+        }
+    }
+
     // -------------------------- view methods -----------------------------
+    /// Whether `operator_id` may transfer all tokens of `owner_id`.
+    pub fn nft_is_approved_for_all(
+        &self,
+        owner_id: AccountId,
+        operator_id: AccountId,
+    ) -> bool {
+        self.operators_internal(&owner_id).contains(&operator_id)
+    }
+
+    /// The accounts that may transfer all tokens of `owner_id`.
+    pub fn nft_operators(
+        &self,
+        owner_id: AccountId,
+    ) -> Vec<AccountId> {
+        self.operators_internal(&owner_id).into_iter().collect()
+    }
+
     /// Returns the most recent `approval_id` for `account_id` on `token_id`.
-    /// If the account doesn't have approval on the token, it will return
-    /// `None`.
+    /// If the account doesn't have approval on the token, or the approval has
+    /// expired, it will return `None`.
     ///
     /// Panics if the token doesn't exist.
     pub fn nft_approval_id(
@@ -174,20 +266,39 @@ impl MintbaseStore {
         token_id: U64,
         account_id: AccountId,
     ) -> Option<u64> {
-        let token = self.nft_token_internal(token_id.into());
-        token.approvals.get(&account_id).cloned()
+        // panics if the token doesn't exist
+        self.nft_token_internal(token_id.into());
+        self.active_token_approvals(token_id.into())
+            .0
+            .get(&account_id)
+            .cloned()
     }
 
     // -------------------------- private methods --------------------------
     // -------------------------- internal methods -------------------------
 
+    /// Require the attached deposit to cover the storage that has been added
+    /// since the storage usage was `usage_before`. Returns the part of the
+    /// deposit that exceeds it.
+    fn charge_storage_since(
+        &self,
+        usage_before: StorageUsage,
+    ) -> Balance {
+        let storage_stake = env::storage_usage().saturating_sub(usage_before) as u128
+            * self.storage_costs.storage_price_per_byte;
+        assert_storage_deposit!(storage_stake);
+        env::attached_deposit() - storage_stake
+    }
+
     /// Called from nft_approve and nft_batch_approve.
     fn approve_internal(
         &mut self,
         token_idu64: u64,
         account_id: &AccountId,
+        scope: &Option<ApprovalScope>,
     ) -> u64 {
-        let mut token = self.nft_token_internal(token_idu64);
+        self.assert_not_migrating();
+        let token = self.nft_token_internal(token_idu64);
         // token.assert_unloaned();
         // token.assert_owned_by_predecessor();
         assert_token_unloaned!(token);
@@ -195,11 +306,180 @@ impl MintbaseStore {
 
         let approval_id = self.num_approved;
         self.num_approved += 1;
-        token.approvals.insert(account_id.clone(), approval_id);
-        self.tokens.insert(&token_idu64, &token);
+        let mut approvals = self.token_approvals_internal(token_idu64);
+        // a new approval replaces the previous one, including its scope
+        match approvals.insert(account_id.clone(), approval_id) {
+            Some(old_approval_id) => {
+                self.approval_scopes.remove(&old_approval_id);
+            },
+            None => self.index_approval(account_id, token_idu64),
+        }
+        self.set_token_approvals(token_idu64, &approvals);
+        if let Some(scope) = scope {
+            near_assert!(
+                !scope.is_expired(),
+                "Cannot approve with an expiry in the past"
+            );
+            self.approval_scopes.insert(&approval_id, scope);
+        }
         approval_id
     }
 
+    /// The operators of `owner_id`.
+    fn operators_internal(
+        &self,
+        owner_id: &AccountId,
+    ) -> HashSet<AccountId> {
+        self.operator_approvals.get(owner_id).unwrap_or_default()
+    }
+
+    /// Whether `account_id` is an operator of the account that owns `token`.
+    /// Tokens that are not owned by an account have no operators.
+    pub(crate) fn is_token_operator(
+        &self,
+        token: &Token,
+        account_id: &AccountId,
+    ) -> bool {
+        match &token.owner_id {
+            Owner::Account(owner_id) => self.operators_internal(owner_id).contains(account_id),
+            _ => false,
+        }
+    }
+
+    /// Enforce the scope of the approval that `account_id` uses to transfer
+    /// `token`, in a sale for `price` if the transfer is a sale. Single-use
+    /// approvals are consumed. Does nothing if `account_id` owns the token.
+    pub(crate) fn use_approval(
+        &mut self,
+        token: &Token,
+        account_id: &AccountId,
+        price: Option<Balance>,
+    ) {
+        if token.is_owned_by(account_id) {
+            return;
+        }
+        let mut approvals = self.token_approvals_internal(token.id);
+        let scope = match approvals
+            .get(account_id)
+            .and_then(|approval_id| self.approval_scopes.get(approval_id))
+        {
+            Some(scope) => scope,
+            None => return,
+        };
+        near_assert!(
+            !scope.is_expired(),
+            "The approval of {} for token {} has expired",
+            account_id,
+            token.id
+        );
+        if let Some(min_price) = scope.min_price {
+            near_assert!(
+                price.map(|price| price >= min_price.0).unwrap_or(false),
+                "The approval of {} for token {} requires a sale for at least {} yoctoNEAR",
+                account_id,
+                token.id,
+                min_price.0
+            );
+        }
+        if scope.single_use {
+            let approval_id = approvals.remove(account_id).unwrap();
+            self.set_token_approvals(token.id, &approvals);
+            self.approval_scopes.remove(&approval_id);
+            self.unindex_approval(account_id, token.id);
+        }
+    }
+
+    /// Remove all approvals on the token with `token_id`, including their
+    /// scopes. Returns whether there were any.
+    pub(crate) fn clear_token_approvals(
+        &mut self,
+        token_id: u64,
+    ) -> bool {
+        match self.token_approvals.remove(&token_id) {
+            Some(approvals) => {
+                for (account_id, approval_id) in approvals.iter() {
+                    self.approval_scopes.remove(approval_id);
+                    self.unindex_approval(account_id, token_id);
+                }
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Add the token with `token_id` to the tokens that `account_id` is
+    /// approved for.
+    pub(crate) fn index_approval(
+        &mut self,
+        account_id: &AccountId,
+        token_id: u64,
+    ) {
+        let mut approved_tree = self.get_or_make_new_approved_tree(account_id);
+        approved_tree.insert(&token_id, &());
+        self.tokens_per_approved.insert(account_id, &approved_tree);
+    }
+
+    /// Remove the token with `token_id` from the tokens that `account_id` is
+    /// approved for.
+    fn unindex_approval(
+        &mut self,
+        account_id: &AccountId,
+        token_id: u64,
+    ) {
+        if let Some(mut approved_tree) = self.tokens_per_approved.get(account_id) {
+            approved_tree.remove(&token_id);
+            if approved_tree.len() == 0 {
+                self.tokens_per_approved.remove(account_id);
+            } else {
+                self.tokens_per_approved.insert(account_id, &approved_tree);
+            }
+        }
+    }
+
+    /// The approvals on the token with `token_id` that have not expired,
+    /// and the scopes of those among them that have been given with one.
+    pub(crate) fn active_token_approvals(
+        &self,
+        token_id: u64,
+    ) -> (HashMap<AccountId, u64>, HashMap<AccountId, ApprovalScope>) {
+        let mut approvals = self.token_approvals_internal(token_id);
+        let mut scopes = HashMap::new();
+        approvals.retain(
+            |account_id, approval_id| match self.approval_scopes.get(approval_id) {
+                Some(scope) if scope.is_expired() => false,
+                Some(scope) => {
+                    scopes.insert(account_id.clone(), scope);
+                    true
+                },
+                None => true,
+            },
+        );
+        (approvals, scopes)
+    }
+
+    /// The approvals on the token with `token_id`, mapping approved accounts
+    /// to their approval IDs.
+    pub(crate) fn token_approvals_internal(
+        &self,
+        token_id: u64,
+    ) -> HashMap<AccountId, u64> {
+        self.token_approvals.get(&token_id).unwrap_or_default()
+    }
+
+    /// Store the approvals on the token with `token_id`, removing the entry
+    /// if there are none left.
+    fn set_token_approvals(
+        &mut self,
+        token_id: u64,
+        approvals: &HashMap<AccountId, u64>,
+    ) {
+        if approvals.is_empty() {
+            self.token_approvals.remove(&token_id);
+        } else {
+            self.token_approvals.insert(&token_id, approvals);
+        }
+    }
+
     /// Same as `nft_is_approved`, but uses internal u64 (u64) typing for
     /// Copy-efficiency.
     pub(crate) fn nft_is_approved_internal(
@@ -208,15 +488,32 @@ impl MintbaseStore {
         approved_account_id: &AccountId,
         approval_id: Option<u64>,
     ) -> bool {
-        if approved_account_id.to_string() == token.owner_id.to_string() {
+        if approved_account_id.to_string() == token.owner_id.to_string()
+            || self.is_token_operator(token, approved_account_id)
+        {
             true
         } else {
             let approval_id = approval_id.expect("approval_id required");
-            let stored_approval = token.approvals.get(approved_account_id);
+            let approvals = self.token_approvals_internal(token.id);
+            let stored_approval = approvals.get(approved_account_id);
             match stored_approval {
                 None => false,
-                Some(&stored_approval_id) => stored_approval_id == approval_id,
+                Some(&stored_approval_id) => {
+                    stored_approval_id == approval_id
+                        && !self
+                            .approval_scopes
+                            .get(&stored_approval_id)
+                            .map(|scope| scope.is_expired())
+                            .unwrap_or(false)
+                },
             }
         }
     }
 }
+
+/// Return `amount` of the attached deposit to the predecessor.
+fn refund_deposit(amount: Balance) {
+    if amount > 0 {
+        Promise::new(env::predecessor_account_id()).transfer(amount);
+    }
+}
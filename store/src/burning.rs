@@ -1,3 +1,4 @@
+use mintbase_deps::constants::gas;
 use mintbase_deps::logging::log_nft_batch_burn;
 use mintbase_deps::near_sdk::json_types::U64;
 use mintbase_deps::near_sdk::{
@@ -5,6 +6,11 @@ use mintbase_deps::near_sdk::{
     env,
     near_bindgen,
     AccountId,
+    Gas,
+};
+use mintbase_deps::utils::{
+    metadata_hash,
+    royalty_hash,
 };
 use mintbase_deps::{
     assert_token_owned_by,
@@ -21,7 +27,9 @@ impl MintbaseStore {
     /// The token will be permanently removed from this contract. Burn each
     /// token_id in `token_ids`.
     ///
-    /// Only the tokens' owner may call this function.
+    /// Only the tokens' owner may call this function. The prepaid gas must
+    /// cover `gas::BURN_PER_TOKEN` per token, on top of `gas::BATCH_BASE`,
+    /// otherwise the method fails before burning.
     #[payable]
     pub fn nft_batch_burn(
         &mut self,
@@ -29,6 +37,7 @@ impl MintbaseStore {
     ) {
         assert_yocto_deposit!();
         assert!(!token_ids.is_empty());
+        assert_batch_gas(token_ids.len() as u64, gas::BURN_PER_TOKEN, Gas(0));
         self.burn_triaged(token_ids, env::predecessor_account_id());
     }
 
@@ -41,6 +50,7 @@ impl MintbaseStore {
         token_ids: Vec<U64>,
         account_id: AccountId,
     ) {
+        self.assert_not_migrating();
         let mut set_owned = self.tokens_per_owner.get(&account_id).expect("none owned");
 
         token_ids.iter().for_each(|&token_id| {
@@ -51,31 +61,45 @@ impl MintbaseStore {
             assert_token_unloaned!(token);
             assert_token_owned_by!(token, &account_id);
 
-            // update the counts on token metadata and royalties stored
-            let metadata_id = self.nft_token_internal(token_id).metadata_id;
+            // update the counts on token metadata and royalties stored, and
+            // drop them from the content index once unused
+            let metadata_id = token.metadata_id;
             let (count, metadata) = self.token_metadata.get(&metadata_id).unwrap();
             if count > 1 {
                 self.token_metadata
                     .insert(&metadata_id, &(count - 1, metadata));
             } else {
                 self.token_metadata.remove(&metadata_id);
+                self.restocked_copies.remove(&metadata_id);
+                let hash = metadata_hash(&metadata);
+                if self.metadata_ids_by_hash.get(&hash) == Some(metadata_id) {
+                    self.metadata_ids_by_hash.remove(&hash);
+                }
             }
-            if let Some(royalty_id) = self.nft_token_internal(token_id).royalty_id {
+            if let Some(royalty_id) = token.royalty_id {
                 let (count, royalty) = self.token_royalty.get(&royalty_id).unwrap();
                 if count > 1 {
                     self.token_royalty
                         .insert(&royalty_id, &(count - 1, royalty));
                 } else {
                     self.token_royalty.remove(&royalty_id);
+                    let hash = royalty_hash(&royalty);
+                    if self.royalty_ids_by_hash.get(&hash) == Some(royalty_id) {
+                        self.royalty_ids_by_hash.remove(&hash);
+                    }
                 }
             }
 
             set_owned.remove(&token_id);
+            self.update_edition_holders(token.metadata_id, Some(&account_id), None, 1);
             self.tokens.remove(&token_id);
+            self.clear_token_approvals(token_id);
+            self.unindex_token(&token);
         });
 
-        if set_owned.is_empty() {
+        if set_owned.len() == 0 {
             self.tokens_per_owner.remove(&account_id);
+            self.holders.remove(&account_id);
         } else {
             self.tokens_per_owner.insert(&account_id, &set_owned);
         }
@@ -83,20 +107,6 @@ impl MintbaseStore {
         log_nft_batch_burn(&token_ids, account_id.to_string());
     }
 
-    /// Get info about the store.
-    pub fn get_info(&self) {
-        let s = format!("owner: {}", self.owner_id);
-        env::log_str(s.as_str());
-        let s = format!("minted: {}", self.tokens_minted);
-        env::log_str(s.as_str());
-        let s = format!("burned: {}", self.tokens_burned);
-        env::log_str(s.as_str());
-        let s = format!("approved: {}", self.num_approved);
-        env::log_str(s.as_str());
-        let s = format!("allow_moves: {}", self.allow_moves);
-        env::log_str(s.as_str());
-    }
-
     // -------------------------- view methods -----------------------------
     // -------------------------- private methods --------------------------
     // -------------------------- internal methods -------------------------
@@ -18,6 +18,8 @@ use mintbase_deps::near_sdk::{
     env,
     near_bindgen,
     AccountId,
+    Balance,
+    Gas,
     Promise,
     PromiseResult,
 };
@@ -51,14 +53,7 @@ impl MintbaseStore {
         memo: Option<String>,
     ) {
         assert_yocto_deposit!();
-        let token_idu64 = token_id.into();
-        let mut token = self.nft_token_internal(token_idu64);
-        let old_owner = token.owner_id.to_string();
-        assert_token_unloaned!(token);
-        assert_token_owned_or_approved!(token, &env::predecessor_account_id(), approval_id);
-
-        self.transfer_internal(&mut token, receiver_id.clone(), true);
-        log_nft_transfer(&receiver_id, token_idu64, &memo, old_owner);
+        self.nft_transfer_internal(receiver_id, token_id.into(), approval_id, memo, None);
     }
 
     #[payable]
@@ -70,11 +65,20 @@ impl MintbaseStore {
         msg: String,
     ) -> Promise {
         assert_yocto_deposit!();
+        self.assert_not_migrating();
         let token_idu64 = token_id.into();
         let mut token = self.nft_token_internal(token_idu64);
         let pred = env::predecessor_account_id();
         assert_token_unloaned!(token);
-        assert_token_owned_or_approved!(token, &pred, approval_id);
+        if !self.is_token_operator(&token, &pred) {
+            assert_token_owned_or_approved!(
+                token,
+                self.token_approvals_internal(token_idu64),
+                &pred,
+                approval_id
+            );
+            self.use_approval(&token, &pred, None);
+        }
         // prevent race condition, temporarily lock-replace owner
         let owner_id = AccountId::new_unchecked(token.owner_id.to_string());
         self.lock_token(&mut token);
@@ -163,13 +167,18 @@ impl MintbaseStore {
 impl MintbaseStore {
     // -------------------------- change methods ---------------------------
 
+    /// Transfer each token in `token_ids` to the paired account. The prepaid
+    /// gas must cover `gas::TRANSFER_PER_TOKEN` per token, on top of
+    /// `gas::BATCH_BASE`, otherwise the method fails before transferring.
     #[payable]
     pub fn nft_batch_transfer(
         &mut self,
         token_ids: Vec<(U64, AccountId)>,
     ) {
         assert_yocto_deposit!();
+        self.assert_not_migrating();
         near_assert!(!token_ids.is_empty(), "Token IDs cannot be empty");
+        assert_batch_gas(token_ids.len() as u64, gas::TRANSFER_PER_TOKEN, Gas(0));
         let pred = env::predecessor_account_id();
         let mut set_owned = self.tokens_per_owner.get(&pred).expect("none owned");
         let (tokens, accounts, old_owners) = token_ids
@@ -197,7 +206,12 @@ impl MintbaseStore {
                 acc.2.push(oid);
                 acc
             });
-        self.tokens_per_owner.insert(&pred, &set_owned);
+        if set_owned.len() == 0 {
+            self.tokens_per_owner.remove(&pred);
+            self.holders.remove(&pred);
+        } else {
+            self.tokens_per_owner.insert(&pred, &set_owned);
+        }
         log_nft_batch_transfer(&tokens, &accounts, old_owners);
     }
 
@@ -207,6 +221,38 @@ impl MintbaseStore {
 
     // -------------------------- internal methods -------------------------
 
+    /// Transfer the token with `token_id` on behalf of the predecessor, who
+    /// must own the token, be an operator of its owner, or use an approval.
+    /// `price` is the sale price if the transfer is part of a sale, as
+    /// required by some approvals.
+    pub(crate) fn nft_transfer_internal(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: u64,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        price: Option<Balance>,
+    ) {
+        self.assert_not_migrating();
+        let mut token = self.nft_token_internal(token_id);
+        let old_owner = token.owner_id.to_string();
+        let pred = env::predecessor_account_id();
+        assert_token_unloaned!(token);
+        // operators need no approval ID, and are not subject to scopes
+        if !self.is_token_operator(&token, &pred) {
+            assert_token_owned_or_approved!(
+                token,
+                self.token_approvals_internal(token_id),
+                &pred,
+                approval_id
+            );
+            self.use_approval(&token, &pred, price);
+        }
+
+        self.transfer_internal(&mut token, receiver_id.clone(), true);
+        log_nft_transfer(&receiver_id, token_id, &memo, old_owner);
+    }
+
     /// Set the owner of `token` to `to` and clear the approvals on the
     /// token. Update the `tokens_per_owner` sets. `remove_prior` is an
     /// optimization on batch removal, in particular useful for batch sending
@@ -226,9 +272,13 @@ impl MintbaseStore {
             None
         };
         token.split_owners = None;
+        // the edition count moves from the previous holder even if the owner
+        // sets are updated by the caller
+        let prior_holder = token_holder(token);
+        self.update_edition_holders(token.metadata_id, prior_holder.as_ref(), Some(&to), 1);
         self.update_tokens_per_owner(token.id, update_set, Some(to.clone()));
         token.owner_id = Owner::Account(to);
-        token.approvals.clear();
+        self.clear_token_approvals(token.id);
         self.tokens.insert(&token.id, token);
     }
 
@@ -264,10 +314,12 @@ impl MintbaseStore {
                 reference: metadata.reference,
                 reference_hash: metadata.reference_hash,
             };
+            let (approved_account_ids, approval_scopes) = self.active_token_approvals(x.id);
             TokenCompliant {
                 token_id: format!("{}", x.id),
                 owner_id: x.owner_id,
-                approved_account_ids: x.approvals,
+                approved_account_ids,
+                approval_scopes,
                 metadata,
                 royalty,
                 split_owners: x.split_owners,
@@ -11,25 +11,26 @@ use crate::*;
 // -------------------- standardized enumeration methods -------------------- //
 #[near_bindgen]
 impl MintbaseStore {
+    /// The number of tokens on this `Store` that have not been burned.
     pub fn nft_total_supply(&self) -> U64 {
-        // TODO: shouldn't this subtract all burned tokens?
-        self.tokens_minted.into()
+        self.live_tokens.len().into()
     }
 
+    /// Page through the tokens that have not been burned, ordered by token
+    /// ID. `from_index` is the position among those tokens, not a token ID,
+    /// and `limit` the maximum number of tokens to return (default: all
+    /// remaining tokens). Reaching a position walks all tokens before it, so
+    /// prefer `nft_tokens_after` for large stores.
     pub fn nft_tokens(
         &self,
         from_index: Option<String>, // default: "0"
-        limit: Option<u64>,         // default: = self.nft_total_supply()
+        limit: Option<u64>,
     ) -> Vec<TokenCompliant> {
-        let from_index: u64 = from_index
-            .unwrap_or_else(|| "0".to_string())
-            .parse()
-            .unwrap();
-        let limit = limit.unwrap_or(self.tokens_minted);
-        (from_index..limit)
-            .into_iter()
-            .flat_map(|token_id| self.nft_token_compliant_internal(token_id))
-            .collect()
+        self.paginate_tokens(
+            &self.live_tokens,
+            parse_from_index(from_index),
+            limit.unwrap_or(u64::MAX),
+        )
     }
 
     pub fn nft_supply_for_owner(
@@ -43,15 +44,19 @@ impl MintbaseStore {
             .into()
     }
 
+    /// Page through the tokens of `account_id`, ordered by token ID.
+    /// Accounts without tokens yield an empty list.
     pub fn nft_tokens_for_owner(
         &self,
         account_id: AccountId,
         from_index: Option<String>,
         limit: Option<usize>,
     ) -> Vec<TokenCompliant> {
-        self.tokens_per_owner
-            .get(&account_id)
-            .expect("no tokens")
+        let owned_set = match self.tokens_per_owner.get(&account_id) {
+            Some(owned_set) => owned_set,
+            None => return vec![],
+        };
+        owned_set
             .iter()
             .skip(
                 from_index
@@ -60,11 +65,241 @@ impl MintbaseStore {
                     .unwrap(),
             )
             .take(limit.unwrap_or(10))
-            .flat_map(|x| self.nft_token_compliant_internal(x))
+            .flat_map(|(x, _)| self.nft_token_compliant_internal(x))
             .collect::<Vec<_>>()
     }
 }
 
 // ------------------ non-standardized enumeration methods ------------------ //
 #[near_bindgen]
-impl MintbaseStore {}
+impl MintbaseStore {
+    /// The number of unburned tokens that share the metadata with
+    /// `metadata_id`, i.e. the remaining copies of an edition.
+    pub fn nft_supply_for_metadata(
+        &self,
+        metadata_id: U64,
+    ) -> U64 {
+        self.tokens_per_metadata
+            .get(&metadata_id.0)
+            .map(|set| set.len())
+            .unwrap_or(0)
+            .into()
+    }
+
+    /// Page through the unburned tokens that share the metadata with
+    /// `metadata_id`, with the same cursor as `nft_tokens_for_owner_after`.
+    pub fn nft_tokens_for_metadata(
+        &self,
+        metadata_id: U64,
+        after_token_id: Option<U64>,
+        limit: Option<u64>,
+    ) -> Vec<TokenCompliant> {
+        match self.tokens_per_metadata.get(&metadata_id.0) {
+            Some(edition_tree) => self.paginate_tokens_after(&edition_tree, after_token_id, limit),
+            None => vec![],
+        }
+    }
+
+    /// The number of unburned tokens that have been minted by `minter_id`.
+    pub fn nft_supply_for_minter(
+        &self,
+        minter_id: AccountId,
+    ) -> U64 {
+        self.tokens_per_minter
+            .get(&minter_id)
+            .map(|set| set.len())
+            .unwrap_or(0)
+            .into()
+    }
+
+    /// Page through the unburned tokens that have been minted by
+    /// `minter_id`, with the same cursor as `nft_tokens_for_owner_after`.
+    pub fn nft_tokens_for_minter(
+        &self,
+        minter_id: AccountId,
+        after_token_id: Option<U64>,
+        limit: Option<u64>,
+    ) -> Vec<TokenCompliant> {
+        match self.tokens_per_minter.get(&minter_id) {
+            Some(minted_tree) => self.paginate_tokens_after(&minted_tree, after_token_id, limit),
+            None => vec![],
+        }
+    }
+
+    /// The number of unburned tokens that originated on another contract.
+    pub fn nft_supply_with_origin(&self) -> U64 {
+        self.tokens_with_origin.len().into()
+    }
+
+    /// Page through the unburned tokens that originated on another
+    /// contract, with the same cursor as `nft_tokens_for_owner_after`.
+    pub fn nft_tokens_with_origin(
+        &self,
+        after_token_id: Option<U64>,
+        limit: Option<u64>,
+    ) -> Vec<TokenCompliant> {
+        self.paginate_tokens_after(&self.tokens_with_origin, after_token_id, limit)
+    }
+
+    /// Page through the tokens that have not been burned, with the same
+    /// cursor as `nft_tokens_for_owner_after`. Unlike `nft_tokens`, each
+    /// page only reads the tokens it returns.
+    pub fn nft_tokens_after(
+        &self,
+        after_token_id: Option<U64>,
+        limit: Option<u64>,
+    ) -> Vec<TokenCompliant> {
+        self.paginate_tokens_after(&self.live_tokens, after_token_id, limit)
+    }
+
+    /// Page through the tokens of `account_id` with a cursor: returns up to
+    /// `limit` tokens with IDs greater than `after_token_id`, ordered by token
+    /// ID. Unlike `from_index`, the cursor stays valid while tokens are
+    /// transferred or burned between pages. Pass the ID of the last token of
+    /// the previous page to get the next page.
+    pub fn nft_tokens_for_owner_after(
+        &self,
+        account_id: AccountId,
+        after_token_id: Option<U64>,
+        limit: Option<u64>,
+    ) -> Vec<TokenCompliant> {
+        match self.tokens_per_owner.get(&account_id) {
+            Some(owned_tree) => self.paginate_tokens_after(&owned_tree, after_token_id, limit),
+            None => vec![],
+        }
+    }
+
+    /// The number of tokens that `account_id` has been approved for. Operator
+    /// approvals are not included.
+    pub fn nft_supply_for_approved(
+        &self,
+        account_id: AccountId,
+    ) -> U64 {
+        self.tokens_per_approved
+            .get(&account_id)
+            .map(|tree| tree.len())
+            .unwrap_or(0)
+            .into()
+    }
+
+    /// Page through the tokens that `account_id` has been approved for, with
+    /// the same cursor as `nft_tokens_for_owner_after`. Approvals that have
+    /// expired but have not been revoked are still listed, and operator
+    /// approvals are not included.
+    pub fn nft_tokens_for_approved(
+        &self,
+        account_id: AccountId,
+        after_token_id: Option<U64>,
+        limit: Option<u64>,
+    ) -> Vec<TokenCompliant> {
+        match self.tokens_per_approved.get(&account_id) {
+            Some(approved_tree) => {
+                self.paginate_tokens_after(&approved_tree, after_token_id, limit)
+            },
+            None => vec![],
+        }
+    }
+
+    /// The number of accounts that hold tokens on this `Store`.
+    pub fn nft_holders_count(&self) -> U64 {
+        self.holders.len().into()
+    }
+
+    /// Page through the accounts that hold tokens on this `Store`, with the
+    /// number of tokens each of them holds. Holders are ordered by account
+    /// ID, and up to `limit` (default: 10) holders after `after_account_id`
+    /// are returned.
+    pub fn nft_holders(
+        &self,
+        after_account_id: Option<AccountId>,
+        limit: Option<u64>,
+    ) -> Vec<(AccountId, U64)> {
+        let limit = limit.unwrap_or(10) as usize;
+        let with_count = |(holder, _): (AccountId, ())| {
+            let count = self.nft_supply_for_owner(holder.clone());
+            (holder, count)
+        };
+        match after_account_id {
+            Some(after_account_id) => self
+                .holders
+                .iter_from(after_account_id)
+                .take(limit)
+                .map(with_count)
+                .collect(),
+            None => self.holders.iter().take(limit).map(with_count).collect(),
+        }
+    }
+
+    /// Page through the accounts that hold copies of the edition with
+    /// `metadata_id`, with the number of copies each of them holds. Holders
+    /// are ordered by account ID, and up to `limit` (default: 10) holders
+    /// after `after_account_id` are returned.
+    pub fn nft_holders_for_metadata(
+        &self,
+        metadata_id: U64,
+        after_account_id: Option<AccountId>,
+        limit: Option<u64>,
+    ) -> Vec<(AccountId, U64)> {
+        let holder_counts = match self.edition_holders.get(&metadata_id.0) {
+            Some(holder_counts) => holder_counts,
+            None => return vec![],
+        };
+        let limit = limit.unwrap_or(10) as usize;
+        match after_account_id {
+            Some(after_account_id) => holder_counts
+                .iter_from(after_account_id)
+                .take(limit)
+                .map(|(holder, count)| (holder, count.into()))
+                .collect(),
+            None => holder_counts
+                .iter()
+                .take(limit)
+                .map(|(holder, count)| (holder, count.into()))
+                .collect(),
+        }
+    }
+}
+
+impl MintbaseStore {
+    /// Get up to `limit` tokens (default: 10) from `token_ids` with IDs
+    /// greater than `after_token_id`.
+    fn paginate_tokens_after(
+        &self,
+        token_ids: &TreeMap<u64, ()>,
+        after_token_id: Option<U64>,
+        limit: Option<u64>,
+    ) -> Vec<TokenCompliant> {
+        let token_ids: Box<dyn Iterator<Item = (u64, ())> + '_> = match after_token_id {
+            // `iter_from` excludes the given key
+            Some(after_token_id) => Box::new(token_ids.iter_from(after_token_id.0)),
+            None => Box::new(token_ids.iter()),
+        };
+        token_ids
+            .take(limit.unwrap_or(10) as usize)
+            .flat_map(|(token_id, _)| self.nft_token_compliant_internal(token_id))
+            .collect()
+    }
+
+    /// Get up to `limit` tokens from `token_ids`, starting at position
+    /// `from_index`.
+    fn paginate_tokens(
+        &self,
+        token_ids: &TreeMap<u64, ()>,
+        from_index: u64,
+        limit: u64,
+    ) -> Vec<TokenCompliant> {
+        token_ids
+            .iter()
+            .skip(from_index as usize)
+            .take(limit as usize)
+            .flat_map(|(token_id, _)| self.nft_token_compliant_internal(token_id))
+            .collect()
+    }
+}
+
+fn parse_from_index(from_index: Option<String>) -> u64 {
+    from_index
+        .unwrap_or_else(|| "0".to_string())
+        .parse()
+        .unwrap()
+}
@@ -1,14 +1,34 @@
+use std::collections::{
+    HashMap,
+    HashSet,
+};
+
 use mintbase_deps::common::{
+    ApprovalScope,
+    MintJob,
     NFTContractMetadata,
+    PendingStoreChange,
     Royalty,
+    RoyaltyArgs,
+    StoreInfo,
+    StoreInitialMint,
+    StoreSettings,
     TokenMetadata,
     TokenMetadataCompliant,
 };
 use mintbase_deps::constants::{
+    gas,
     storage_stake,
     StorageCosts,
+    MINIMUM_FREE_STORAGE_STAKE,
+    ROYALTY_UPPER_LIMIT,
     YOCTO_PER_BYTE,
 };
+use mintbase_deps::migrations::{
+    read_state_version,
+    write_state_version,
+    MintbaseStoreV1,
+};
 use mintbase_deps::near_assert;
 use mintbase_deps::near_sdk::borsh::{
     self,
@@ -17,6 +37,8 @@ use mintbase_deps::near_sdk::borsh::{
 };
 use mintbase_deps::near_sdk::collections::{
     LookupMap,
+    TreeMap,
+    UnorderedMap,
     UnorderedSet,
 };
 use mintbase_deps::near_sdk::json_types::{
@@ -29,6 +51,9 @@ use mintbase_deps::near_sdk::{
     ext_contract,
     near_bindgen,
     AccountId,
+    Balance,
+    CryptoHash,
+    Gas,
     StorageUsage,
 };
 use mintbase_deps::token::{
@@ -46,15 +71,26 @@ mod core;
 mod enumeration;
 /// Implementing metadata as [described in the Nomicon](https://nomicon.io/Standards/NonFungibleToken/Metadata).
 mod metadata;
+/// Implementing the backfill of indexes after state migrations.
+mod migration;
 /// Implementing any methods related to minting.
 mod minting;
 /// Implementing any methods related to store ownership.
 mod ownership;
 /// Implementing payouts as [described in the Nomicon](https://nomicon.io/Standards/NonFungibleToken/Payout).
 mod payout;
+/// Implementing the timelock for administrative store changes.
+mod timelock;
+/// Implementing upgrades of the store code via the factory.
+mod upgrade;
 
 // ----------------------------- smart contract ----------------------------- //
 
+/// Version of the state layout defined by `MintbaseStore`. Needs to be bumped
+/// whenever the layout changes, with the previous layout moving to
+/// `mintbase_deps::migrations`.
+pub const STATE_VERSION: u32 = 2;
+
 // TODO: shouldn't this be PanicOnDefault?
 #[near_bindgen]
 #[derive(BorshDeserialize, BorshSerialize)]
@@ -66,29 +102,32 @@ pub struct MintbaseStore {
     /// If a Minter mints more than one token at a time, all tokens will
     /// share the same `TokenMetadata`. It's more storage-efficient to store
     /// that `TokenMetadata` once, rather than to copy the data on each
-    /// Token. The key is generated from `tokens_minted`. The map keeps count
-    /// of how many copies of this token remain, so that the element may be
-    /// dropped when the number reaches zero (ie, when tokens are burnt).
-    pub token_metadata: LookupMap<u64, (u16, TokenMetadata)>,
+    /// Token. Later mints with identical metadata share it as well. The key
+    /// is generated from `tokens_minted` of the first mint. The map keeps
+    /// count of how many copies of this token remain, so that the element
+    /// may be dropped when the number reaches zero (ie, when tokens are
+    /// burnt).
+    pub token_metadata: LookupMap<u64, (u64, TokenMetadata)>,
     /// If a Minter mints more than one token at a time, all tokens will
     /// share the same `Royalty`. It's more storage-efficient to store that
-    /// `Royalty` once, rather than to copy the data on each Token. The key
-    /// is generated from `tokens_minted`. The map keeps count of how many
-    /// copies of this token remain, so that the element may be dropped when
-    /// the number reaches zero (ie, when tokens are burnt).
-    pub token_royalty: LookupMap<u64, (u16, Royalty)>,
+    /// `Royalty` once, rather than to copy the data on each Token. Later
+    /// mints with an identical royalty share it as well. The key is
+    /// generated from `tokens_minted` of the first mint. The map keeps count
+    /// of how many copies of this token remain, so that the element may be
+    /// dropped when the number reaches zero (ie, when tokens are burnt).
+    pub token_royalty: LookupMap<u64, (u64, Royalty)>,
     /// Tokens this Store has minted, excluding those that have been burned.
     pub tokens: LookupMap<u64, Token>,
-    /// A mapping from each user to the tokens owned by that user. The owner
-    /// of the token is also stored on the token itself.
-    pub tokens_per_owner: LookupMap<AccountId, UnorderedSet<u64>>,
+    /// A mapping from each user to the tokens owned by that user, ordered by
+    /// token ID. The owner of the token is also stored on the token itself.
+    pub tokens_per_owner: LookupMap<AccountId, TreeMap<u64, ()>>,
     /// A map from a token_id of a token on THIS contract to a set of tokens,
     /// that may be on ANY contract. If the owned-token is on this contract,
     /// the id will have format "<u64>". If the token is on another contract,
     /// the token will have format "<u64>:account_id"
     pub composeables: LookupMap<String, UnorderedSet<String>>,
-    /// The number of tokens this `Store` has minted. Used to generate
-    /// `TokenId`s.
+    /// The number of token IDs this `Store` has assigned, including those
+    /// reserved by mint jobs. Used to generate `TokenId`s.
     pub tokens_minted: u64,
     /// The number of tokens this `Store` has burned.
     pub tokens_burned: u64,
@@ -104,6 +143,84 @@ pub struct MintbaseStore {
     pub storage_costs: StorageCosts,
     /// If false, disallow users to call `nft_move`.
     pub allow_moves: bool,
+    /// Delay in nanoseconds between scheduling and executing administrative
+    /// changes. If zero, administrative changes take effect immediately.
+    pub admin_delay: u64,
+    /// Administrative changes that have been scheduled, but not yet executed
+    /// or cancelled.
+    pub pending_changes: UnorderedMap<u64, PendingStoreChange>,
+    /// The number of administrative changes that have been scheduled on this
+    /// `Store`. Used to generate change IDs.
+    pub num_scheduled_changes: u64,
+    /// If true, the owner of the `Factory` may upgrade the code of this
+    /// `Store`. The store owner may always upgrade.
+    pub allow_factory_upgrades: bool,
+    /// If true, disallow minters to call `nft_batch_mint`.
+    pub minting_paused: bool,
+    /// Upper limit for the royalty percentage (out of 10_000) of newly
+    /// minted tokens.
+    pub max_royalty_percentage: u32,
+    /// Royalty for tokens that are minted without explicit royalty.
+    pub default_royalty: Option<Royalty>,
+    /// If true, the owner of the `Factory` may trigger emergency actions on
+    /// this `Store`, such as pausing minting.
+    pub allow_factory_emergency_actions: bool,
+    /// IDs of all tokens that have not been burned, ordered by token ID and
+    /// used for enumeration.
+    pub live_tokens: TreeMap<u64, ()>,
+    /// IDs of the tokens that share the same metadata, keyed by metadata ID
+    /// and ordered by token ID.
+    pub tokens_per_metadata: LookupMap<u64, TreeMap<u64, ()>>,
+    /// IDs of the tokens that have been minted by an account, ordered by
+    /// token ID.
+    pub tokens_per_minter: LookupMap<AccountId, TreeMap<u64, ()>>,
+    /// IDs of the tokens that originated on another contract, ordered by
+    /// token ID.
+    pub tokens_with_origin: TreeMap<u64, ()>,
+    /// Accounts that hold tokens on this `Store`, i.e. the keys of
+    /// `tokens_per_owner`, ordered by account ID.
+    pub holders: TreeMap<AccountId, ()>,
+    /// Accounts that may transfer a token other than its owner, with their
+    /// approval IDs. Kept apart from `tokens`, so that token records do not
+    /// grow with approvals. Tokens without approvals have no entry.
+    pub token_approvals: LookupMap<u64, HashMap<AccountId, u64>>,
+    /// IDs of the entries in `token_metadata`, keyed by the sha256 hash of
+    /// their content, so that identical metadata is stored only once.
+    pub metadata_ids_by_hash: LookupMap<CryptoHash, u64>,
+    /// IDs of the entries in `token_royalty`, keyed by the sha256 hash of
+    /// their content, so that identical royalties are stored only once.
+    pub royalty_ids_by_hash: LookupMap<CryptoHash, u64>,
+    /// Mint jobs that have been created but not yet completed.
+    pub mint_jobs: LookupMap<u64, MintJob>,
+    /// The number of mint jobs ever created, used to assign job IDs.
+    pub num_mint_jobs: u64,
+    /// Constraints on approvals, keyed by approval ID. Approvals without
+    /// constraints have no entry.
+    pub approval_scopes: LookupMap<u64, ApprovalScope>,
+    /// Accounts that may transfer all tokens of an owner, keyed by owner.
+    /// Owners without operators have no entry.
+    pub operator_approvals: LookupMap<AccountId, HashSet<AccountId>>,
+    /// IDs of the tokens that an account has been approved for, ordered by
+    /// token ID. The reverse of `token_approvals`, without operators.
+    pub tokens_per_approved: LookupMap<AccountId, TreeMap<u64, ()>>,
+    /// The number of copies of an edition that each account holds, keyed by
+    /// metadata ID and ordered by account ID. Accounts without copies have
+    /// no entry.
+    pub edition_holders: LookupMap<u64, TreeMap<AccountId, u64>>,
+    /// The deposit that remains reserved for the tokens of each unfinished
+    /// mint job, released as the tokens are minted. Completed jobs have no
+    /// entry.
+    pub mint_job_deposits: LookupMap<u64, Balance>,
+    /// The sum of `mint_job_deposits`, which does not cover the storage of
+    /// anything but the tokens of those jobs.
+    pub reserved_mint_job_deposit: Balance,
+    /// The number of mint jobs that have not been completed.
+    pub num_pending_mint_jobs: u64,
+    /// The number of copies that later mints of identical metadata have
+    /// added to an edition, keyed by metadata ID. Stored metadata is never
+    /// changed, so its `copies` only count the first mint. Editions that have
+    /// not been restocked have no entry.
+    pub restocked_copies: LookupMap<u64, u64>,
 }
 
 impl Default for MintbaseStore {
@@ -116,36 +233,85 @@ impl Default for MintbaseStore {
 impl MintbaseStore {
     /// Create a new `Store`. `new` validates the `store_description`.
     ///
-    /// The `Store` is initialized with the owner as a `minter`.
+    /// The `Store` is initialized with the owner and `minters` as minters.
+    /// Optionally, `settings` and `default_royalty` may be set, and tokens
+    /// may be minted by the owner via `initial_mint`, requiring sufficient
+    /// balance on the store account.
     #[init]
     pub fn new(
         metadata: NFTContractMetadata,
         owner_id: AccountId,
+        minters: Option<Vec<AccountId>>,
+        settings: Option<StoreSettings>,
+        default_royalty: Option<RoyaltyArgs>,
+        initial_mint: Option<StoreInitialMint>,
     ) -> Self {
         near_assert!(!env::state_exists(), "This store is already initialized!");
-        let mut minters = UnorderedSet::new(b"a".to_vec());
-        minters.insert(&owner_id);
+        let mut store = Self::new_internal(metadata, owner_id.clone());
+        write_state_version(STATE_VERSION);
 
-        Self {
-            minters,
-            metadata,
-            token_metadata: LookupMap::new(b"b".to_vec()),
-            token_royalty: LookupMap::new(b"c".to_vec()),
-            tokens: LookupMap::new(b"d".to_vec()),
-            tokens_per_owner: LookupMap::new(b"e".to_vec()),
-            composeables: LookupMap::new(b"f".to_vec()),
-            tokens_minted: 0,
-            tokens_burned: 0,
-            num_approved: 0,
-            owner_id,
-            storage_costs: StorageCosts::new(YOCTO_PER_BYTE), // 10^19
-            allow_moves: true,
+        for minter in minters.unwrap_or_default() {
+            store.grant_minter_internal(&minter);
         }
+        if let Some(settings) = settings {
+            store.apply_store_settings(settings);
+        }
+        store.default_royalty = default_royalty.map(Royalty::new);
+        if let Some(mint) = initial_mint {
+            store.mint_internal(
+                owner_id.clone(),
+                mint.owner_id.unwrap_or(owner_id),
+                mint.metadata,
+                mint.num_to_mint,
+                mint.royalty_args,
+                mint.split_owners,
+            );
+        }
+        store
     }
 
     // -------------------------- change methods ---------------------------
     // -------------------------- view methods -----------------------------
 
+    /// Configuration and counters of this `Store`, including its storage
+    /// usage and balance.
+    pub fn get_info(&self) -> StoreInfo {
+        let storage_usage = env::storage_usage();
+        let balance = env::account_balance();
+        let free_storage_stake = balance
+            .saturating_sub(storage_usage as u128 * env::storage_byte_cost())
+            .saturating_sub(self.reserved_mint_job_deposit);
+        StoreInfo {
+            owner_id: self.owner_id.clone(),
+            minter_count: self.minters.len(),
+            tokens_minted: self.tokens_minted.into(),
+            tokens_burned: self.tokens_burned.into(),
+            num_approved: self.num_approved.into(),
+            settings: self.get_store_settings(),
+            admin_delay: self.admin_delay.into(),
+            num_pending_changes: self.pending_changes.len(),
+            storage_price_per_byte: self.storage_costs.storage_price_per_byte.into(),
+            storage_usage: storage_usage.into(),
+            balance: balance.into(),
+            free_storage_stake: free_storage_stake.into(),
+            reserved_mint_job_deposit: self.reserved_mint_job_deposit.into(),
+            num_pending_mint_jobs: self.num_pending_mint_jobs,
+            minimum_free_storage_stake: MINIMUM_FREE_STORAGE_STAKE.into(),
+            state_version: read_state_version(),
+        }
+    }
+
+    /// The current policy settings of this `Store`.
+    pub fn get_store_settings(&self) -> StoreSettings {
+        StoreSettings {
+            allow_moves: Some(self.allow_moves),
+            minting_paused: Some(self.minting_paused),
+            max_royalty_percentage: Some(self.max_royalty_percentage),
+            allow_factory_upgrades: Some(self.allow_factory_upgrades),
+            allow_factory_emergency_actions: Some(self.allow_factory_emergency_actions),
+        }
+    }
+
     /// Get the holder of the token. The token may be owned by:
     /// - a normal account: return that account.
     /// - a lent out account : in that case, return the loan holder.
@@ -165,34 +331,34 @@ impl MintbaseStore {
         }
     }
 
-    /// A non-indexed implementation. `from_index` and `limit are removed, so as
-    /// to support the:
-    ///
-    /// `tokens_per_owner: LookupMap<AccountId, UnorderedSet<TokenId>>`
-    ///
-    /// type. They may be used in an implementation if the type is instead:
-    ///
-    /// `tokens_per_owner: LookupMap<AccountId, Vector<TokenId>>`
+    /// All token IDs of `account_id`, in ascending order, or none if the
+    /// account holds no tokens. For large collections, use
+    /// `nft_tokens_for_owner_after` instead.
     pub fn nft_tokens_for_owner_set(
         &self,
         account_id: AccountId,
     ) -> Vec<u64> {
         self.tokens_per_owner
             .get(&account_id)
-            .expect("no tokens")
-            .iter()
-            .collect()
+            .map(|owned_set| owned_set.iter().map(|(token_id, _)| token_id).collect())
+            .unwrap_or_default()
+    }
+
+    /// Get the version of the state layout this `Store` is using.
+    pub fn get_state_version(&self) -> u32 {
+        read_state_version()
     }
 
-    /// Get the number of unburned copies of the token in existance.
+    /// Get the number of unburned copies of the token in existance. Copies
+    /// that a mint job has yet to mint are not counted.
     pub fn get_token_remaining_copies(
         &self,
         token_id: U64,
-    ) -> u16 {
-        self.token_metadata
+    ) -> u64 {
+        self.tokens_per_metadata
             .get(&self.nft_token_internal(token_id.into()).metadata_id)
-            .expect("bad metadata_id")
-            .0
+            .map(|edition_set| edition_set.len())
+            .unwrap_or(0)
     }
 
     // -------------------------- private methods --------------------------
@@ -201,11 +367,25 @@ impl MintbaseStore {
     /// elements of the state should be copied over. This method may only be
     /// called by the holder of the Store public key, in this case the
     /// Factory.
+    ///
+    /// The state is migrated from whichever layout version is currently
+    /// stored to `STATE_VERSION`. Indexes that would exceed the gas of this
+    /// call are backfilled afterwards via `continue_migration`.
     #[private]
     #[init(ignore_state)]
     pub fn migrate(metadata: NFTContractMetadata) -> Self {
-        let old = env::state_read().expect("ohno ohno state");
-        Self { metadata, ..old }
+        let version = read_state_version();
+        near_assert!(
+            version <= STATE_VERSION,
+            "Cannot migrate from state version {} to older version {}",
+            version,
+            STATE_VERSION
+        );
+        let mut store = Self::read(version);
+        store.metadata = metadata;
+        write_state_version(STATE_VERSION);
+        store.start_backfill(version);
+        store
     }
 
     // -------------------------- internal methods -------------------------
@@ -214,12 +394,15 @@ impl MintbaseStore {
     /// `nft_move` on this contract, AND on other contracts targetting this
     /// contract. `nft_move` allows the user to burn a token they own on one
     /// contract, and re-mint it on another contract.
+    ///
+    /// If the store is timelocked, the change needs to be scheduled instead.
     #[payable]
     pub fn set_allow_moves(
         &mut self,
         state: bool,
     ) {
         self.assert_store_owner();
+        self.assert_not_timelocked();
         self.allow_moves = state;
     }
 
@@ -242,16 +425,50 @@ impl MintbaseStore {
         if let Some(from) = from {
             let mut old_owner_owned_set = self.tokens_per_owner.get(&from).unwrap();
             old_owner_owned_set.remove(&token_id);
-            if old_owner_owned_set.is_empty() {
+            if old_owner_owned_set.len() == 0 {
                 self.tokens_per_owner.remove(&from);
+                self.holders.remove(&from);
             } else {
                 self.tokens_per_owner.insert(&from, &old_owner_owned_set);
             }
         }
         if let Some(to) = to {
             let mut new_owner_owned_set = self.get_or_make_new_owner_set(&to);
-            new_owner_owned_set.insert(&token_id);
+            new_owner_owned_set.insert(&token_id, &());
             self.tokens_per_owner.insert(&to, &new_owner_owned_set);
+            self.holders.insert(&to, &());
+        }
+    }
+
+    /// Internal
+    /// Move `count` copies of the edition with `metadata_id` from one
+    /// holder's count to another's.
+    fn update_edition_holders(
+        &mut self,
+        metadata_id: u64,
+        from: Option<&AccountId>,
+        to: Option<&AccountId>,
+        count: u64,
+    ) {
+        let mut holder_counts = self.get_or_make_new_edition_holders(metadata_id);
+        if let Some(from) = from {
+            match holder_counts.get(from) {
+                Some(held) if held > count => {
+                    holder_counts.insert(from, &(held - count));
+                },
+                _ => {
+                    holder_counts.remove(from);
+                },
+            }
+        }
+        if let Some(to) = to {
+            let held = holder_counts.get(to).unwrap_or(0);
+            holder_counts.insert(to, &(held + count));
+        }
+        if holder_counts.len() == 0 {
+            self.edition_holders.remove(&metadata_id);
+        } else {
+            self.edition_holders.insert(&metadata_id, &holder_counts);
         }
     }
 
@@ -294,18 +511,164 @@ impl MintbaseStore {
     // }
 
     /// If an account_id has never owned tokens on this store, we must
-    /// construct an `UnorderedSet` for them. If they have owned tokens on
-    /// this store, get that set.
+    /// construct a `TreeMap` for them. If they have owned tokens on this
+    /// store, get that tree.
     /// Internal
     pub(crate) fn get_or_make_new_owner_set(
         &self,
         account_id: &AccountId,
-    ) -> UnorderedSet<u64> {
-        self.tokens_per_owner.get(account_id).unwrap_or_else(|| {
-            let mut prefix: Vec<u8> = vec![b'j'];
-            prefix.extend_from_slice(account_id.as_bytes());
-            UnorderedSet::new(prefix)
-        })
+    ) -> TreeMap<u64, ()> {
+        self.tokens_per_owner
+            .get(account_id)
+            .unwrap_or_else(|| TreeMap::new(owner_tree_prefix(account_id)))
+    }
+
+    pub(crate) fn get_or_make_new_metadata_set(
+        &self,
+        metadata_id: u64,
+    ) -> TreeMap<u64, ()> {
+        self.tokens_per_metadata
+            .get(&metadata_id)
+            .unwrap_or_else(|| TreeMap::new(edition_tree_prefix(metadata_id)))
+    }
+
+    pub(crate) fn get_or_make_new_minter_set(
+        &self,
+        account_id: &AccountId,
+    ) -> TreeMap<u64, ()> {
+        self.tokens_per_minter
+            .get(account_id)
+            .unwrap_or_else(|| TreeMap::new(minter_tree_prefix(account_id)))
+    }
+
+    pub(crate) fn get_or_make_new_edition_holders(
+        &self,
+        metadata_id: u64,
+    ) -> TreeMap<AccountId, u64> {
+        self.edition_holders
+            .get(&metadata_id)
+            .unwrap_or_else(|| TreeMap::new(edition_holders_prefix(metadata_id)))
+    }
+
+    pub(crate) fn get_or_make_new_approved_tree(
+        &self,
+        account_id: &AccountId,
+    ) -> TreeMap<u64, ()> {
+        self.tokens_per_approved
+            .get(account_id)
+            .unwrap_or_else(|| TreeMap::new(approved_tree_prefix(account_id)))
+    }
+
+    /// Remove `token` from the enumeration indexes, except for the owner
+    /// index.
+    pub(crate) fn unindex_token(
+        &mut self,
+        token: &Token,
+    ) {
+        self.live_tokens.remove(&token.id);
+        if let Some(mut edition_set) = self.tokens_per_metadata.get(&token.metadata_id) {
+            edition_set.remove(&token.id);
+            if edition_set.len() == 0 {
+                self.tokens_per_metadata.remove(&token.metadata_id);
+            } else {
+                self.tokens_per_metadata
+                    .insert(&token.metadata_id, &edition_set);
+            }
+        }
+        if let Some(mut minted_set) = self.tokens_per_minter.get(&token.minter) {
+            minted_set.remove(&token.id);
+            if minted_set.len() == 0 {
+                self.tokens_per_minter.remove(&token.minter);
+            } else {
+                self.tokens_per_minter.insert(&token.minter, &minted_set);
+            }
+        }
+        self.tokens_with_origin.remove(&token.id);
+    }
+
+    /// The initial state of a `Store` with `owner_id` as only minter.
+    fn new_internal(
+        metadata: NFTContractMetadata,
+        owner_id: AccountId,
+    ) -> Self {
+        let mut minters = UnorderedSet::new(b"a".to_vec());
+        minters.insert(&owner_id);
+
+        Self {
+            minters,
+            metadata,
+            token_metadata: LookupMap::new(b"b".to_vec()),
+            token_royalty: LookupMap::new(b"c".to_vec()),
+            tokens: LookupMap::new(b"d".to_vec()),
+            tokens_per_owner: LookupMap::new(b"r".to_vec()),
+            composeables: LookupMap::new(b"f".to_vec()),
+            tokens_minted: 0,
+            tokens_burned: 0,
+            num_approved: 0,
+            owner_id,
+            storage_costs: StorageCosts::new(YOCTO_PER_BYTE), // 10^19
+            allow_moves: true,
+            admin_delay: 0,
+            pending_changes: UnorderedMap::new(b"g".to_vec()),
+            num_scheduled_changes: 0,
+            allow_factory_upgrades: false,
+            minting_paused: false,
+            max_royalty_percentage: ROYALTY_UPPER_LIMIT,
+            default_royalty: None,
+            allow_factory_emergency_actions: false,
+            live_tokens: TreeMap::new(b"A".to_vec()),
+            tokens_per_metadata: LookupMap::new(b"B".to_vec()),
+            tokens_per_minter: LookupMap::new(b"D".to_vec()),
+            tokens_with_origin: TreeMap::new(b"F".to_vec()),
+            holders: TreeMap::new(b"p".to_vec()),
+            token_approvals: LookupMap::new(b"s".to_vec()),
+            metadata_ids_by_hash: LookupMap::new(b"t".to_vec()),
+            royalty_ids_by_hash: LookupMap::new(b"u".to_vec()),
+            mint_jobs: LookupMap::new(b"v".to_vec()),
+            num_mint_jobs: 0,
+            approval_scopes: LookupMap::new(b"w".to_vec()),
+            operator_approvals: LookupMap::new(b"x".to_vec()),
+            tokens_per_approved: LookupMap::new(b"y".to_vec()),
+            edition_holders: LookupMap::new(b"G".to_vec()),
+            mint_job_deposits: LookupMap::new(b"I".to_vec()),
+            reserved_mint_job_deposit: 0,
+            num_pending_mint_jobs: 0,
+            restocked_copies: LookupMap::new(b"J".to_vec()),
+        }
+    }
+
+    /// Apply the given `settings`, keeping the current values for omitted
+    /// settings.
+    fn apply_store_settings(
+        &mut self,
+        settings: StoreSettings,
+    ) {
+        if let Some(allow_moves) = settings.allow_moves {
+            self.allow_moves = allow_moves;
+        }
+        if let Some(minting_paused) = settings.minting_paused {
+            self.minting_paused = minting_paused;
+        }
+        if let Some(percentage) = settings.max_royalty_percentage {
+            minting::assert_valid_max_royalty_percentage(percentage);
+            self.max_royalty_percentage = percentage;
+        }
+        if let Some(allow) = settings.allow_factory_upgrades {
+            self.allow_factory_upgrades = allow;
+        }
+        if let Some(allow) = settings.allow_factory_emergency_actions {
+            self.allow_factory_emergency_actions = allow;
+        }
+    }
+
+    /// Read the state as the current layout, migrating it from the layout
+    /// indicated by `version` if necessary.
+    fn read(version: u32) -> Self {
+        if version < STATE_VERSION {
+            MintbaseStoreV1::read(version).into()
+        } else {
+            env::state_read().expect("ohno ohno state")
+        }
     }
 
     /// Internal
@@ -331,6 +694,136 @@ impl MintbaseStore {
     }
 }
 
+// ---------------------------- state migrations ---------------------------- //
+
+impl From<MintbaseStoreV1> for MintbaseStore {
+    fn from(old: MintbaseStoreV1) -> Self {
+        Self {
+            minters: old.minters,
+            metadata: old.metadata,
+            // metadata, royalties and tokens keep their prefixes and are
+            // rewritten in place by the backfill
+            token_metadata: LookupMap::new(b"b".to_vec()),
+            token_royalty: LookupMap::new(b"c".to_vec()),
+            tokens: LookupMap::new(b"d".to_vec()),
+            // filled by the backfill, which drops the old owner sets
+            tokens_per_owner: LookupMap::new(b"r".to_vec()),
+            composeables: old.composeables,
+            tokens_minted: old.tokens_minted,
+            tokens_burned: old.tokens_burned,
+            num_approved: old.num_approved,
+            owner_id: old.owner_id,
+            storage_costs: old.storage_costs,
+            allow_moves: old.allow_moves,
+            admin_delay: 0,
+            pending_changes: UnorderedMap::new(b"g".to_vec()),
+            num_scheduled_changes: 0,
+            allow_factory_upgrades: false,
+            minting_paused: false,
+            max_royalty_percentage: ROYALTY_UPPER_LIMIT,
+            default_royalty: None,
+            allow_factory_emergency_actions: false,
+            // filled by the backfill
+            live_tokens: TreeMap::new(b"A".to_vec()),
+            tokens_per_metadata: LookupMap::new(b"B".to_vec()),
+            tokens_per_minter: LookupMap::new(b"D".to_vec()),
+            tokens_with_origin: TreeMap::new(b"F".to_vec()),
+            holders: TreeMap::new(b"p".to_vec()),
+            token_approvals: LookupMap::new(b"s".to_vec()),
+            metadata_ids_by_hash: LookupMap::new(b"t".to_vec()),
+            royalty_ids_by_hash: LookupMap::new(b"u".to_vec()),
+            mint_jobs: LookupMap::new(b"v".to_vec()),
+            num_mint_jobs: 0,
+            approval_scopes: LookupMap::new(b"w".to_vec()),
+            operator_approvals: LookupMap::new(b"x".to_vec()),
+            // filled by the backfill
+            tokens_per_approved: LookupMap::new(b"y".to_vec()),
+            edition_holders: LookupMap::new(b"G".to_vec()),
+            mint_job_deposits: LookupMap::new(b"I".to_vec()),
+            reserved_mint_job_deposit: 0,
+            num_pending_mint_jobs: 0,
+            restocked_copies: LookupMap::new(b"J".to_vec()),
+        }
+    }
+}
+
+/// Fail early unless the remaining prepaid gas covers `num_items` items of a
+/// batch at `gas_per_item` each, on top of the fixed costs of the batch and
+/// `reserved` gas, e.g. for cross-contract calls. The panic message reports
+/// how many items would fit.
+pub(crate) fn assert_batch_gas(
+    num_items: u64,
+    gas_per_item: Gas,
+    reserved: Gas,
+) {
+    let max_items = batch_capacity(gas_per_item, reserved);
+    near_assert!(
+        num_items <= max_items,
+        "Not enough gas for {} items, the prepaid gas of {} Tgas fits at most {} items",
+        num_items,
+        env::prepaid_gas().0 / 10u64.pow(12),
+        max_items
+    );
+}
+
+/// The number of batch items at `gas_per_item` each that the remaining
+/// prepaid gas covers, on top of the fixed costs of the batch and `reserved`
+/// gas.
+pub(crate) fn batch_capacity(
+    gas_per_item: Gas,
+    reserved: Gas,
+) -> u64 {
+    let available = (env::prepaid_gas().0)
+        .saturating_sub(env::used_gas().0)
+        .saturating_sub(gas::BATCH_BASE.0)
+        .saturating_sub(reserved.0);
+    available / gas_per_item.0
+}
+
+/// Storage prefix of the `tokens_per_owner` tree of `account_id`.
+fn owner_tree_prefix(account_id: &AccountId) -> Vec<u8> {
+    let mut prefix: Vec<u8> = vec![b'q'];
+    prefix.extend_from_slice(account_id.as_bytes());
+    prefix
+}
+
+/// Storage prefix of the `tokens_per_metadata` tree of `metadata_id`.
+fn edition_tree_prefix(metadata_id: u64) -> Vec<u8> {
+    let mut prefix: Vec<u8> = vec![b'C'];
+    prefix.extend_from_slice(&metadata_id.to_le_bytes());
+    prefix
+}
+
+/// Storage prefix of the `edition_holders` tree of `metadata_id`.
+fn edition_holders_prefix(metadata_id: u64) -> Vec<u8> {
+    let mut prefix: Vec<u8> = vec![b'H'];
+    prefix.extend_from_slice(&metadata_id.to_le_bytes());
+    prefix
+}
+
+/// Storage prefix of the `tokens_per_minter` tree of `account_id`.
+fn minter_tree_prefix(account_id: &AccountId) -> Vec<u8> {
+    let mut prefix: Vec<u8> = vec![b'E'];
+    prefix.extend_from_slice(account_id.as_bytes());
+    prefix
+}
+
+/// Storage prefix of the `tokens_per_approved` tree of `account_id`.
+fn approved_tree_prefix(account_id: &AccountId) -> Vec<u8> {
+    let mut prefix: Vec<u8> = vec![b'z'];
+    prefix.extend_from_slice(account_id.as_bytes());
+    prefix
+}
+
+/// The account that holds `token` in `tokens_per_owner`, if any. Composed
+/// tokens are held by other tokens.
+pub(crate) fn token_holder(token: &Token) -> Option<AccountId> {
+    match &token.owner_id {
+        Owner::Account(account_id) | Owner::Lock(account_id) => Some(account_id.clone()),
+        Owner::TokenId(_) | Owner::CrossKey(_) => None,
+    }
+}
+
 // ----------------------- contract interface modules ----------------------- //
 
 #[ext_contract(store_self)]
@@ -370,4 +863,12 @@ pub trait NonFungibleResolveTransfer {
         token_id: String,
         approved_account_ids: Option<Vec<String>>,
     );
+
+    /// Delete this `Store` once the factory has retired it.
+    #[private]
+    fn on_retire_store(
+        &mut self,
+        beneficiary_id: AccountId,
+        minting_paused: bool,
+    );
 }
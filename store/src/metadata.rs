@@ -78,10 +78,16 @@ impl MintbaseStore {
         token_id: U64,
         // TODO: why not `TokenMetadataCompliant`?
     ) -> TokenMetadata {
-        self.token_metadata
-            .get(&self.nft_token_internal(token_id.into()).metadata_id)
-            .expect("bad metadata_id")
-            .1
+        let metadata_id = self.nft_token_internal(token_id.into()).metadata_id;
+        let (_, mut metadata) = self
+            .token_metadata
+            .get(&metadata_id)
+            .expect("bad metadata_id");
+        // the stored copies only count the first mint of the edition
+        if let Some(restocked) = self.restocked_copies.get(&metadata_id) {
+            metadata.copies = Some(metadata.copies.unwrap_or(0) + restocked);
+        }
+        metadata
     }
 
     /// The Token URI is generated to index the token on whatever distributed
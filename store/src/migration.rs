@@ -0,0 +1,213 @@
+use mintbase_deps::common::MigrationProgress;
+use mintbase_deps::migrations::{
+    is_migrating,
+    read_migration_progress,
+    write_migration_progress,
+    TokenMetadataV1,
+    TokenV1,
+};
+use mintbase_deps::near_panic;
+use mintbase_deps::near_sdk::{
+    self,
+    near_bindgen,
+    AccountId,
+};
+use mintbase_deps::utils::{
+    metadata_hash,
+    royalty_hash,
+};
+
+use crate::*;
+
+#[cfg(test)]
+mod tests;
+
+#[near_bindgen]
+impl MintbaseStore {
+    // -------------------------- change methods ---------------------------
+    /// Continue the backfill that follows the migration from the baseline
+    /// layout, processing at most `limit` token IDs. Returns whether the
+    /// backfill is complete. Until then, most methods on the store will
+    /// panic.
+    ///
+    /// Anyone may call this, as the backfill only ever needs to be done.
+    pub fn continue_migration(
+        &mut self,
+        limit: u64,
+    ) -> bool {
+        let progress = read_migration_progress()
+            .unwrap_or_else(|| near_panic!("There is no migration in progress"));
+        let old_metadata: LookupMap<u64, (u16, TokenMetadataV1)> = LookupMap::new(b"b".to_vec());
+        let old_royalty: LookupMap<u64, (u16, Royalty)> = LookupMap::new(b"c".to_vec());
+        let old_tokens: LookupMap<u64, TokenV1> = LookupMap::new(b"d".to_vec());
+        let mut old_tokens_per_owner = legacy_tokens_per_owner();
+
+        let cursor = progress.cursor.0;
+        let end = cursor.saturating_add(limit).min(self.tokens_minted);
+        for token_id in cursor..end {
+            // metadata and royalties are stored under the ID of the first
+            // token of their mint
+            if let Some((count, metadata)) = old_metadata.get(&token_id) {
+                self.backfill_metadata(token_id, u64::from(count), metadata.into());
+            }
+            if let Some((count, royalty)) = old_royalty.get(&token_id) {
+                self.backfill_royalty(token_id, u64::from(count), royalty);
+            }
+            if let Some(old_token) = old_tokens.get(&token_id) {
+                self.backfill_token(old_token, &mut old_tokens_per_owner);
+            }
+        }
+
+        if end < self.tokens_minted {
+            write_migration_progress(Some(&MigrationProgress { cursor: end.into() }));
+            false
+        } else {
+            write_migration_progress(None);
+            true
+        }
+    }
+
+    /// Drop the owner sets that the baseline layout kept for `account_ids`.
+    /// The backfill drops the sets of accounts holding tokens, but accounts
+    /// that held tokens in the past may have kept an empty set, and these
+    /// cannot be enumerated on chain.
+    ///
+    /// Anyone may call this once the backfill is complete, as the old sets
+    /// are no longer used by then.
+    pub fn clear_legacy_owner_sets(
+        &mut self,
+        account_ids: Vec<AccountId>,
+    ) {
+        self.assert_not_migrating();
+        let mut old_tokens_per_owner = legacy_tokens_per_owner();
+        for account_id in account_ids.iter() {
+            if let Some(mut owned_set) = old_tokens_per_owner.remove(account_id) {
+                owned_set.clear();
+            }
+        }
+    }
+
+    // -------------------------- view methods -----------------------------
+    /// Progress of the backfill that follows a state migration, or `None`
+    /// if there is none running.
+    pub fn get_migration_progress(&self) -> Option<MigrationProgress> {
+        read_migration_progress()
+    }
+
+    // -------------------------- internal methods -------------------------
+    /// Schedule the backfill after migrating from `from_version`. Only the
+    /// baseline layout needs one, and only if it has minted tokens.
+    pub(crate) fn start_backfill(
+        &self,
+        from_version: u32,
+    ) {
+        if from_version < STATE_VERSION && self.tokens_minted > 0 {
+            write_migration_progress(Some(&MigrationProgress { cursor: 0.into() }));
+        }
+    }
+
+    /// Panics while a backfill is running, as the indexes cannot be relied
+    /// upon until it completes. Change methods that touch tokens call this,
+    /// except for `nft_resolve_transfer`, which finishes a transfer that has
+    /// started before the migration. Views are not guarded, as they cannot
+    /// corrupt the indexes.
+    pub(crate) fn assert_not_migrating(&self) {
+        if is_migrating() {
+            near_panic!("This store is being migrated, call `continue_migration` first");
+        }
+    }
+
+    /// Rewrite the metadata stored under `lookup_id` with a `u64` count, in
+    /// place, and index it by its content. If several entries share the same
+    /// content, new mints reuse the first.
+    fn backfill_metadata(
+        &mut self,
+        lookup_id: u64,
+        count: u64,
+        metadata: TokenMetadata,
+    ) {
+        let hash = metadata_hash(&metadata);
+        if !self.metadata_ids_by_hash.contains_key(&hash) {
+            self.metadata_ids_by_hash.insert(&hash, &lookup_id);
+        }
+        self.token_metadata.insert(&lookup_id, &(count, metadata));
+    }
+
+    /// Rewrite the royalty stored under `lookup_id` with a `u64` count, in
+    /// place, and index it by its content.
+    fn backfill_royalty(
+        &mut self,
+        lookup_id: u64,
+        count: u64,
+        royalty: Royalty,
+    ) {
+        let hash = royalty_hash(&royalty);
+        if !self.royalty_ids_by_hash.contains_key(&hash) {
+            self.royalty_ids_by_hash.insert(&hash, &lookup_id);
+        }
+        self.token_royalty.insert(&lookup_id, &(count, royalty));
+    }
+
+    /// Rewrite `old_token` without approvals, in place, and add it to the
+    /// indexes. Its approvals move to `token_approvals`, and the token moves
+    /// from the set of its holder into a tree, dropping the set once it is
+    /// empty.
+    fn backfill_token(
+        &mut self,
+        old_token: TokenV1,
+        old_tokens_per_owner: &mut LookupMap<AccountId, UnorderedSet<u64>>,
+    ) {
+        let (token, approvals) = old_token.into_token_and_approvals();
+        let token_id = token.id;
+        self.tokens.insert(&token_id, &token);
+        if !approvals.is_empty() {
+            for account_id in approvals.keys() {
+                self.index_approval(account_id, token_id);
+            }
+            self.token_approvals.insert(&token_id, &approvals);
+        }
+
+        self.live_tokens.insert(&token_id, &());
+        let mut edition_tree = self.get_or_make_new_metadata_set(token.metadata_id);
+        edition_tree.insert(&token_id, &());
+        self.tokens_per_metadata
+            .insert(&token.metadata_id, &edition_tree);
+        let mut minted_tree = self.get_or_make_new_minter_set(&token.minter);
+        minted_tree.insert(&token_id, &());
+        self.tokens_per_minter.insert(&token.minter, &minted_tree);
+        if token.origin_key.is_some() {
+            self.tokens_with_origin.insert(&token_id, &());
+        }
+
+        if let Some(holder) = token_holder(&token) {
+            let mut owned_tree = self.get_or_make_new_owner_set(&holder);
+            owned_tree.insert(&token_id, &());
+            self.tokens_per_owner.insert(&holder, &owned_tree);
+            self.holders.insert(&holder, &());
+            self.update_edition_holders(token.metadata_id, None, Some(&holder), 1);
+            remove_from_legacy_set(old_tokens_per_owner, &holder, token_id);
+        }
+    }
+}
+
+/// The owner sets of the baseline layout.
+fn legacy_tokens_per_owner() -> LookupMap<AccountId, UnorderedSet<u64>> {
+    LookupMap::new(b"e".to_vec())
+}
+
+/// Remove `token_id` from the set of `account_id` in `sets`, dropping the
+/// set once it is empty.
+fn remove_from_legacy_set(
+    sets: &mut LookupMap<AccountId, UnorderedSet<u64>>,
+    account_id: &AccountId,
+    token_id: u64,
+) {
+    if let Some(mut set) = sets.get(account_id) {
+        set.remove(&token_id);
+        if set.len() == 0 {
+            sets.remove(account_id);
+        } else {
+            sets.insert(account_id, &set);
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Migration of a store in the baseline layout to the current layout,
+//! including the backfill. The baseline store is written with the baseline
+//! layouts from `mintbase_deps::migrations`, under the storage prefixes that
+//! the baseline contract used: Alice has minted two copies of an edition and
+//! a single token, which has been burned since. One copy has been
+//! transferred to Bob, and each remaining token has an approval.
+use std::collections::HashMap;
+
+use mintbase_deps::migrations::{
+    MintbaseStoreV1,
+    TokenMetadataV1,
+    TokenV1,
+};
+use mintbase_deps::near_sdk::test_utils::VMContextBuilder;
+use mintbase_deps::near_sdk::testing_env;
+use mintbase_deps::token::{
+    ComposeableStats,
+    TokenCompliant,
+};
+
+use super::*;
+
+fn alice() -> AccountId {
+    "alice.near".parse().unwrap()
+}
+
+fn bob() -> AccountId {
+    "bob.near".parse().unwrap()
+}
+
+fn carol() -> AccountId {
+    "carol.near".parse().unwrap()
+}
+
+fn store_metadata() -> NFTContractMetadata {
+    NFTContractMetadata {
+        spec: "nft-1.0.0".to_string(),
+        name: "store".to_string(),
+        symbol: "ALICE".to_string(),
+        ..Default::default()
+    }
+}
+
+fn token_metadata(
+    title: &str,
+    copies: u16,
+) -> TokenMetadataV1 {
+    TokenMetadataV1 {
+        title: Some(title.to_string()),
+        description: None,
+        media: None,
+        media_hash: None,
+        copies: Some(copies),
+        expires_at: None,
+        starts_at: None,
+        extra: None,
+        reference: None,
+        reference_hash: None,
+    }
+}
+
+fn token(
+    id: u64,
+    owner_id: AccountId,
+    metadata_id: u64,
+    approved_account_id: AccountId,
+    approval_id: u64,
+) -> TokenV1 {
+    TokenV1 {
+        id,
+        owner_id: Owner::Account(owner_id),
+        approvals: HashMap::from([(approved_account_id, approval_id)]),
+        metadata_id,
+        royalty_id: None,
+        split_owners: None,
+        minter: alice(),
+        loan: None,
+        composeable_stats: ComposeableStats {
+            local_depth: 0,
+            cross_contract_children: 0,
+        },
+        origin_key: None,
+    }
+}
+
+/// Insert `token_id` into the baseline owner set of `account_id`.
+fn insert_owned(
+    tokens_per_owner: &mut LookupMap<AccountId, UnorderedSet<u64>>,
+    account_id: &AccountId,
+    token_id: Option<u64>,
+) {
+    let mut prefix: Vec<u8> = vec![b'j'];
+    prefix.extend_from_slice(account_id.as_bytes());
+    let mut owned_set = tokens_per_owner
+        .get(account_id)
+        .unwrap_or_else(|| UnorderedSet::new(prefix));
+    if let Some(token_id) = token_id {
+        owned_set.insert(&token_id);
+    }
+    tokens_per_owner.insert(account_id, &owned_set);
+}
+
+/// Write the baseline store as contract state, without a state version.
+fn write_baseline_store() {
+    let mut minters = UnorderedSet::new(b"a".to_vec());
+    minters.insert(&alice());
+
+    let mut token_metadata_map = LookupMap::new(b"b".to_vec());
+    token_metadata_map.insert(&0, &(2u16, token_metadata("Edition", 2)));
+    token_metadata_map.insert(&2, &(1u16, token_metadata("Single", 1)));
+
+    let mut tokens = LookupMap::new(b"d".to_vec());
+    tokens.insert(&0, &token(0, alice(), 0, carol(), 0));
+    tokens.insert(&1, &token(1, bob(), 0, carol(), 1));
+
+    let mut tokens_per_owner = LookupMap::new(b"e".to_vec());
+    insert_owned(&mut tokens_per_owner, &alice(), Some(0));
+    insert_owned(&mut tokens_per_owner, &bob(), Some(1));
+    // Carol held the burned token, which left her with an empty set
+    insert_owned(&mut tokens_per_owner, &carol(), None);
+
+    env::state_write(&MintbaseStoreV1 {
+        minters,
+        metadata: store_metadata(),
+        token_metadata: token_metadata_map,
+        token_royalty: LookupMap::new(b"c".to_vec()),
+        tokens,
+        tokens_per_owner,
+        composeables: LookupMap::new(b"f".to_vec()),
+        tokens_minted: 3,
+        tokens_burned: 1,
+        num_approved: 2,
+        owner_id: alice(),
+        storage_costs: StorageCosts::new(YOCTO_PER_BYTE),
+        allow_moves: true,
+    });
+}
+
+fn token_ids(tokens: Vec<TokenCompliant>) -> Vec<String> {
+    tokens.into_iter().map(|token| token.token_id).collect()
+}
+
+#[test]
+fn migrates_baseline_store() {
+    testing_env!(VMContextBuilder::new().build());
+    write_baseline_store();
+
+    let mut store = MintbaseStore::migrate(store_metadata());
+    assert_eq!(read_state_version(), STATE_VERSION);
+    assert!(store.get_migration_progress().is_some());
+
+    // one token ID per call, with views on backfilled tokens in between
+    assert!(!store.continue_migration(1));
+    assert_eq!(store.nft_total_supply(), U64(1));
+    assert_eq!(store.nft_token(U64(0)).unwrap().token_id, "0");
+    let mut calls = 1;
+    while !store.continue_migration(1) {
+        calls += 1;
+    }
+    assert_eq!(calls, 2);
+    assert!(store.get_migration_progress().is_none());
+
+    // settings that were introduced after the baseline have their defaults
+    assert_eq!(store.owner_id, alice());
+    assert!(store.minters.contains(&alice()));
+    assert_eq!(store.admin_delay, 0);
+    assert!(!store.minting_paused);
+    assert!(!store.allow_factory_upgrades);
+
+    // tokens, metadata and approvals have been rewritten
+    assert_eq!(store.nft_total_supply(), U64(2));
+    let token = store.nft_token(U64(1)).unwrap();
+    assert!(matches!(token.owner_id, Owner::Account(owner_id) if owner_id == bob()));
+    assert_eq!(token.metadata.title.as_deref(), Some("Edition"));
+    assert_eq!(token.metadata.copies, Some(2));
+    assert_eq!(token.approved_account_ids, HashMap::from([(carol(), 1)]));
+    assert!(store.nft_is_approved(U64(0), carol(), Some(0)));
+
+    // indexes have been filled
+    assert_eq!(token_ids(store.nft_tokens_after(None, None)), ["0", "1"]);
+    assert_eq!(
+        token_ids(store.nft_tokens_for_owner_after(alice(), None, None)),
+        ["0"]
+    );
+    assert_eq!(store.nft_supply_for_owner(bob()), U64(1));
+    assert_eq!(
+        token_ids(store.nft_tokens_for_metadata(U64(0), None, None)),
+        ["0", "1"]
+    );
+    assert_eq!(
+        token_ids(store.nft_tokens_for_minter(alice(), None, None)),
+        ["0", "1"]
+    );
+    assert_eq!(
+        token_ids(store.nft_tokens_for_approved(carol(), None, None)),
+        ["0", "1"]
+    );
+    assert_eq!(store.nft_holders_count(), U64(2));
+    assert_eq!(
+        store.nft_holders(None, None),
+        [(alice(), U64(1)), (bob(), U64(1))]
+    );
+    assert_eq!(
+        store.nft_holders_for_metadata(U64(0), None, None),
+        [(alice(), U64(1)), (bob(), U64(1))]
+    );
+
+    // the owner sets of holders have been dropped, others can be cleared
+    let old_tokens_per_owner = legacy_tokens_per_owner();
+    assert!(!old_tokens_per_owner.contains_key(&alice()));
+    assert!(!old_tokens_per_owner.contains_key(&bob()));
+    assert!(old_tokens_per_owner.contains_key(&carol()));
+    store.clear_legacy_owner_sets(vec![carol()]);
+    assert!(!old_tokens_per_owner.contains_key(&carol()));
+
+    // new mints reuse the migrated metadata
+    let lookup_id = store
+        .metadata_ids_by_hash
+        .get(&metadata_hash(&token_metadata("Edition", 2).into()));
+    assert_eq!(lookup_id, Some(0));
+}
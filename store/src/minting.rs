@@ -1,4 +1,5 @@
 use mintbase_deps::common::{
+    MintJob,
     Royalty,
     RoyaltyArgs,
     SplitBetweenUnparsed,
@@ -6,23 +7,36 @@ use mintbase_deps::common::{
     TokenMetadata,
 };
 use mintbase_deps::constants::{
+    gas,
+    storage_bytes,
     MAX_LEN_PAYOUT,
     MINIMUM_FREE_STORAGE_STAKE,
+    ROYALTY_UPPER_LIMIT,
 };
 use mintbase_deps::logging::{
     log_grant_minter,
     log_nft_batch_mint,
     log_revoke_minter,
 };
-use mintbase_deps::near_assert;
+use mintbase_deps::near_sdk::borsh::BorshSerialize;
+use mintbase_deps::near_sdk::json_types::U64;
 use mintbase_deps::near_sdk::{
     self,
     env,
     near_bindgen,
     AccountId,
     Balance,
+    Gas,
 };
 use mintbase_deps::token::Token;
+use mintbase_deps::utils::{
+    metadata_hash,
+    royalty_hash,
+};
+use mintbase_deps::{
+    near_assert,
+    near_panic,
+};
 
 use crate::*;
 
@@ -36,10 +50,14 @@ impl MintbaseStore {
     /// Restrictions:
     /// - Only minters may call this function.
     /// - `owner_id` must be a valid Near address.
-    /// - Because of logging limits, this method may mint at most 99 tokens per call.
+    /// - The prepaid gas must cover `gas::MINT_PER_TOKEN` per token, on top of
+    ///   `gas::BATCH_BASE`. Otherwise, the method fails before minting.
     /// - 1.0 >= `royalty_f` >= 0.0. `royalty_f` is ignored if `royalty` is `None`.
     /// - If a `royalty` is provided, percentages **must** be non-negative and add to one.
+    /// - If no `royalty` is provided, the default royalty of the store applies.
+    /// - The royalty percentage may not exceed the cap set on the store.
     /// - The maximum length of the royalty mapping is 50.
+    /// - Minting must not be paused.
     ///
     /// This method is the most significant increase of storage costs on this
     /// contract. Minters are expected to manage their own storage costs.
@@ -52,11 +70,6 @@ impl MintbaseStore {
         royalty_args: Option<RoyaltyArgs>,
         split_owners: Option<SplitBetweenUnparsed>,
     ) {
-        near_assert!(num_to_mint > 0, "No tokens to mint");
-        near_assert!(
-            num_to_mint <= 125,
-            "Cannot mint more than 125 tokens due to gas limits"
-        ); // upper gas limit
         near_assert!(
             env::attached_deposit() >= 1,
             "Requires deposit of at least 1 yoctoNEAR"
@@ -67,99 +80,195 @@ impl MintbaseStore {
             "{} is not allowed to mint on this store",
             minter_id
         );
+        self.mint_internal(
+            minter_id,
+            owner_id,
+            metadata,
+            num_to_mint,
+            royalty_args,
+            split_owners,
+        );
+    }
 
-        // Calculating storage consuption upfront saves gas if the transaction
-        // were to fail later.
-        let covered_storage = env::account_balance()
-            - (env::storage_usage() as u128 * self.storage_costs.storage_price_per_byte);
-        let (metadata, md_size) = TokenMetadata::from_with_size(metadata, num_to_mint);
-        let roy_len = royalty_args
-            .as_ref()
-            .map(|pre_roy| {
-                let len = pre_roy.split_between.len();
-                len as u32
-            })
-            .unwrap_or(0);
-        let split_len = split_owners
-            .as_ref()
-            .map(|pre_split| {
-                let len = pre_split.len();
-                len as u32
-            })
-            // if there is no split map, there still is an owner, thus default to 1
-            .unwrap_or(1);
+    /// Create a job that mints `num_to_mint` copies of a token to
+    /// `owner_id`, for batches that do not fit into a single transaction.
+    /// The token IDs are reserved right away, and the attached deposit must
+    /// cover the storage of all tokens and of the job itself. The tokens are
+    /// then minted in chunks via `continue_mint_job`, and their share of the
+    /// deposit stays reserved for them until then. Returns the job ID.
+    ///
+    /// Subject to the same restrictions as `nft_batch_mint`, except for the
+    /// gas limit.
+    #[payable]
+    pub fn create_mint_job(
+        &mut self,
+        owner_id: AccountId,
+        metadata: TokenMetadata,
+        num_to_mint: u64,
+        royalty_args: Option<RoyaltyArgs>,
+        split_owners: Option<SplitBetweenUnparsed>,
+    ) -> U64 {
+        let minter_id = env::predecessor_account_id();
         near_assert!(
-            roy_len + split_len <= MAX_LEN_PAYOUT,
-            "Number of payout addresses may not exceed {}",
-            MAX_LEN_PAYOUT
+            self.minters.contains(&minter_id),
+            "{} is not allowed to mint on this store",
+            minter_id
         );
-        let expected_storage_consumption: Balance =
-            self.storage_cost_to_mint(num_to_mint, md_size, roy_len, split_len);
+        let (checked_royalty, checked_split) =
+            self.check_mint_args(num_to_mint, royalty_args, split_owners);
+        let (metadata, _) = TokenMetadata::from_with_size(metadata, num_to_mint);
+
+        let first_token_id = self.tokens_minted;
+        let mut job = MintJob {
+            id: self.num_mint_jobs.into(),
+            minter_id,
+            owner_id,
+            metadata_id: first_token_id.into(),
+            royalty_id: checked_royalty.as_ref().map(|_| first_token_id.into()),
+            split_owners: checked_split,
+            first_token_id: first_token_id.into(),
+            total: num_to_mint.into(),
+            minted: 0.into(),
+        };
+        // the job itself and its entry in `mint_job_deposits`
+        let job_usage = (1 + 8 + job.try_to_vec().unwrap().len()) as StorageUsage
+            + (1 + 8 + 16) as StorageUsage
+            + 2 * storage_bytes::RECORD;
+        let tokens_deposit: Balance = self.storage_usage_to_mint(
+            &job.minter_id,
+            &job.owner_id,
+            &metadata,
+            num_to_mint,
+            checked_royalty.as_ref(),
+            job.split_owners.as_ref(),
+        ) as u128
+            * self.storage_costs.storage_price_per_byte;
+        let required_deposit =
+            tokens_deposit + job_usage as u128 * self.storage_costs.storage_price_per_byte;
         near_assert!(
-            covered_storage >= expected_storage_consumption,
-            "This mint would exceed the current storage coverage of {} yoctoNEAR. Requires at least {} yoctoNEAR",
-            covered_storage,
-            expected_storage_consumption
+            env::attached_deposit() >= required_deposit,
+            "Creating this mint job requires a deposit of at least {} yoctoNEAR",
+            required_deposit
         );
 
-        let checked_royalty = royalty_args.map(Royalty::new);
-        let checked_split = split_owners.map(SplitOwners::new);
-
-        let mut owned_set = self.get_or_make_new_owner_set(&owner_id);
-
-        // Lookup Id is used by the token to lookup Royalty and Metadata fields on
-        // the contract (to avoid unnecessary duplication)
-        let lookup_id: u64 = self.tokens_minted;
-        let royalty_id = checked_royalty.clone().map(|royalty| {
-            self.token_royalty
-                .insert(&lookup_id, &(num_to_mint as u16, royalty));
-            lookup_id
-        });
-
-        let meta_ref = metadata.reference.as_ref().map(|s| s.to_string());
-        let meta_extra = metadata.extra.as_ref().map(|s| s.to_string());
-        self.token_metadata
-            .insert(&lookup_id, &(num_to_mint as u16, metadata));
-
-        // Mint em up hot n fresh with a side of vegan bacon
-        (0..num_to_mint).for_each(|i| {
-            let token_id = self.tokens_minted + i;
-            let token = Token::new(
-                owner_id.clone(),
-                token_id,
-                lookup_id,
-                royalty_id,
-                checked_split.clone(),
-                minter_id.clone(),
-            );
-            owned_set.insert(&token_id);
-            self.tokens.insert(&token_id, &token);
+        // metadata and royalty are stored right away, the remaining deposit
+        // is reserved for the tokens
+        let usage_before = env::storage_usage();
+        job.royalty_id = checked_royalty.map(|royalty| {
+            self.insert_royalty(first_token_id, royalty, num_to_mint)
+                .into()
         });
+        job.metadata_id = self
+            .insert_metadata(first_token_id, metadata, num_to_mint)
+            .into();
+        let content_deposit = (env::storage_usage() - usage_before) as u128
+            * self.storage_costs.storage_price_per_byte;
         self.tokens_minted += num_to_mint;
-        self.tokens_per_owner.insert(&owner_id, &owned_set);
-
-        let minted = self.tokens_minted;
+        self.num_mint_jobs += 1;
+        self.mint_jobs.insert(&job.id.0, &job);
+        self.reserve_mint_job_deposit(job.id.0, tokens_deposit.saturating_sub(content_deposit));
+        job.id
+    }
 
-        // check if sufficient storage stake (e.g. 0.5 NEAR) remains
-        let used_storage_stake: Balance = env::storage_usage() as u128 * env::storage_byte_cost();
-        let free_storage_stake: Balance = env::account_balance() - used_storage_stake;
+    /// Mint the next chunk of tokens of the mint job with `job_id`, as many
+    /// as the prepaid gas allows at `gas::MINT_PER_TOKEN` per token. Any
+    /// account may call this, as the storage has been paid on job creation.
+    /// The part of the job's deposit that covers the chunk is released from
+    /// the reserve. Returns the number of tokens that remain to be minted.
+    ///
+    /// Fails if minting is paused or the job is already complete, and under
+    /// the same storage conditions as `nft_batch_mint`.
+    pub fn continue_mint_job(
+        &mut self,
+        job_id: U64,
+    ) -> U64 {
+        self.assert_not_migrating();
+        near_assert!(!self.minting_paused, "Minting is paused on this store");
+        let mut job = self
+            .mint_jobs
+            .get(&job_id.0)
+            .unwrap_or_else(|| near_panic!("No mint job with ID {}", job_id.0));
+        let remaining = job.total.0 - job.minted.0;
+        near_assert!(remaining > 0, "Mint job {} is already complete", job_id.0);
+        let num_to_mint = remaining.min(batch_capacity(gas::MINT_PER_TOKEN, Gas(0)));
         near_assert!(
-            free_storage_stake > MINIMUM_FREE_STORAGE_STAKE,
-            "A minimum of {} yoctoNEAR is required as free contract balance to allow updates (currently: {})",
-            MINIMUM_FREE_STORAGE_STAKE,
-            free_storage_stake
+            num_to_mint > 0,
+            "Not enough gas to mint any tokens of mint job {}",
+            job_id.0
         );
 
-        log_nft_batch_mint(
-            minted - num_to_mint,
-            minted - 1,
-            minter_id.as_ref(),
-            owner_id.as_ref(),
-            &checked_royalty,
-            &checked_split,
-            &meta_ref,
-            &meta_extra,
+        self.release_mint_job_deposit(job_id.0, num_to_mint, remaining);
+        let (_, metadata) = self.token_metadata.get(&job.metadata_id.0).unwrap();
+        let royalty = job
+            .royalty_id
+            .map(|id| self.token_royalty.get(&id.0).unwrap().1);
+        self.assert_storage_covered(self.storage_usage_to_mint(
+            &job.minter_id,
+            &job.owner_id,
+            &metadata,
+            num_to_mint,
+            royalty.as_ref(),
+            job.split_owners.as_ref(),
+        ));
+
+        self.mint_tokens(
+            &job.minter_id,
+            &job.owner_id,
+            job.metadata_id.0,
+            job.royalty_id.map(|id| id.0),
+            &job.split_owners,
+            job.first_token_id.0 + job.minted.0,
+            num_to_mint,
         );
+        job.minted = (job.minted.0 + num_to_mint).into();
+        self.mint_jobs.insert(&job_id.0, &job);
+        self.assert_free_storage_stake();
+        (remaining - num_to_mint).into()
+    }
+
+    /// Pause or resume minting on this `Store`.
+    ///
+    /// Only the store owner may call this function. If the store is
+    /// timelocked, the change needs to be scheduled instead.
+    #[payable]
+    pub fn set_minting_paused(
+        &mut self,
+        paused: bool,
+    ) {
+        self.assert_store_owner();
+        self.assert_not_timelocked();
+        self.minting_paused = paused;
+    }
+
+    /// Set the upper limit for the royalty percentage (out of 10_000) of
+    /// newly minted tokens. Existing tokens are not affected.
+    ///
+    /// Only the store owner may call this function. If the store is
+    /// timelocked, the change needs to be scheduled instead.
+    #[payable]
+    pub fn set_max_royalty_percentage(
+        &mut self,
+        percentage: u32,
+    ) {
+        self.assert_store_owner();
+        self.assert_not_timelocked();
+        assert_valid_max_royalty_percentage(percentage);
+        self.max_royalty_percentage = percentage;
+    }
+
+    /// Set the royalty that applies to tokens minted without explicit
+    /// royalty. Existing tokens are not affected.
+    ///
+    /// Only the store owner may call this function. If the store is
+    /// timelocked, the change needs to be scheduled instead.
+    #[payable]
+    pub fn set_default_royalty(
+        &mut self,
+        royalty_args: Option<RoyaltyArgs>,
+    ) {
+        self.assert_store_owner();
+        self.assert_not_timelocked();
+        self.default_royalty = royalty_args.map(Royalty::new);
     }
 
     /// Modify the minting privileges of `account_id`. Minters are able to
@@ -178,7 +287,7 @@ impl MintbaseStore {
         self.grant_minter_internal(&account_id)
     }
 
-    fn grant_minter_internal(
+    pub(crate) fn grant_minter_internal(
         &mut self,
         account_id: &AccountId,
     ) {
@@ -192,17 +301,19 @@ impl MintbaseStore {
     /// mint tokens on this `Store`. The current `Store` owner cannot revoke
     /// themselves.
     ///
-    /// Only the store owner may call this function.
+    /// Only the store owner may call this function. If the store is
+    /// timelocked, the revocation needs to be scheduled instead.
     #[payable]
     pub fn revoke_minter(
         &mut self,
         account_id: AccountId,
     ) {
         self.assert_store_owner();
+        self.assert_not_timelocked();
         self.revoke_minter_internal(&account_id);
     }
 
-    fn revoke_minter_internal(
+    pub(crate) fn revoke_minter_internal(
         &mut self,
         account_id: &AccountId,
     ) {
@@ -243,6 +354,7 @@ impl MintbaseStore {
         }
 
         if let Some(revoke_ids) = revoke {
+            self.assert_not_timelocked();
             for account_id in revoke_ids {
                 self.revoke_minter_internal(&account_id)
             }
@@ -265,26 +377,488 @@ impl MintbaseStore {
         self.minters.iter().collect()
     }
 
+    /// The mint job with `job_id`, including its progress. Completed jobs
+    /// are kept, with `minted` equal to `total`.
+    pub fn get_mint_job(
+        &self,
+        job_id: U64,
+    ) -> Option<MintJob> {
+        self.mint_jobs.get(&job_id.0)
+    }
+
+    /// The storage stake in yoctoNEAR that `nft_batch_mint` with the given
+    /// arguments would consume. Minting requires the store to hold this
+    /// amount as free balance, and more than `MINIMUM_FREE_STORAGE_STAKE`
+    /// to remain free afterwards.
+    ///
+    /// The optional `owner_id` and `minter_id` make the estimate exact, as
+    /// the owner and minter indexes are keyed by account ID, so their size
+    /// depends on the length of both IDs, and on whether the accounts already
+    /// hold or minted tokens on this store. `owner_id` defaults to the owner
+    /// of the store, and `minter_id` to `owner_id`.
+    pub fn estimate_mint_deposit(
+        &self,
+        metadata: TokenMetadata,
+        num_to_mint: u64,
+        royalty_args: Option<RoyaltyArgs>,
+        split_owners: Option<SplitBetweenUnparsed>,
+        owner_id: Option<AccountId>,
+        minter_id: Option<AccountId>,
+    ) -> U128 {
+        let owner_id = owner_id.unwrap_or_else(|| self.owner_id.clone());
+        let (metadata, _) = TokenMetadata::from_with_size(metadata, num_to_mint);
+        let royalty = royalty_args
+            .map(Royalty::new)
+            .or_else(|| self.default_royalty.clone());
+        let split_owners = split_owners.map(SplitOwners::new);
+        let usage = self.storage_usage_to_mint(
+            minter_id.as_ref().unwrap_or(&owner_id),
+            &owner_id,
+            &metadata,
+            num_to_mint,
+            royalty.as_ref(),
+            split_owners.as_ref(),
+        );
+        (usage as u128 * self.storage_costs.storage_price_per_byte).into()
+    }
+
     // -------------------------- private methods --------------------------
     // -------------------------- internal methods -------------------------
 
-    /// Get the storage in bytes to mint `num_tokens` each with
-    /// `metadata_storage` and `len_map` royalty receivers.
-    /// Internal
-    fn storage_cost_to_mint(
+    /// Mint `num_to_mint` copies of a token on behalf of `minter_id`. Callers
+    /// of this method MUST validate that `minter_id` is allowed to mint.
+    pub(crate) fn mint_internal(
+        &mut self,
+        minter_id: AccountId,
+        owner_id: AccountId,
+        metadata: TokenMetadata,
+        num_to_mint: u64,
+        royalty_args: Option<RoyaltyArgs>,
+        split_owners: Option<SplitBetweenUnparsed>,
+    ) {
+        let (checked_royalty, checked_split) =
+            self.check_mint_args(num_to_mint, royalty_args, split_owners);
+        assert_batch_gas(num_to_mint, gas::MINT_PER_TOKEN, Gas(0));
+
+        // Calculating storage consuption upfront saves gas if the transaction
+        // were to fail later.
+        let (metadata, _) = TokenMetadata::from_with_size(metadata, num_to_mint);
+        self.assert_storage_covered(self.storage_usage_to_mint(
+            &minter_id,
+            &owner_id,
+            &metadata,
+            num_to_mint,
+            checked_royalty.as_ref(),
+            checked_split.as_ref(),
+        ));
+
+        // Lookup Id is used by the token to lookup Royalty and Metadata fields on
+        // the contract (to avoid unnecessary duplication). Metadata and
+        // royalties that have been stored before are reused instead.
+        let lookup_id: u64 = self.tokens_minted;
+        let royalty_id =
+            checked_royalty.map(|royalty| self.insert_royalty(lookup_id, royalty, num_to_mint));
+        let metadata_id = self.insert_metadata(lookup_id, metadata, num_to_mint);
+
+        self.tokens_minted += num_to_mint;
+        self.mint_tokens(
+            &minter_id,
+            &owner_id,
+            metadata_id,
+            royalty_id,
+            &checked_split,
+            lookup_id,
+            num_to_mint,
+        );
+        self.assert_free_storage_stake();
+    }
+
+    /// Assert that the balance which is neither used for storage nor reserved
+    /// for mint jobs covers `expected_usage` bytes of storage.
+    fn assert_storage_covered(
+        &self,
+        expected_usage: StorageUsage,
+    ) {
+        let covered_storage = env::account_balance()
+            .saturating_sub(
+                env::storage_usage() as u128 * self.storage_costs.storage_price_per_byte,
+            )
+            .saturating_sub(self.reserved_mint_job_deposit);
+        let expected_storage_consumption: Balance =
+            expected_usage as u128 * self.storage_costs.storage_price_per_byte;
+        near_assert!(
+            covered_storage >= expected_storage_consumption,
+            "This mint would exceed the current storage coverage of {} yoctoNEAR. Requires at least {} yoctoNEAR",
+            covered_storage,
+            expected_storage_consumption
+        );
+    }
+
+    /// Assert that sufficient storage stake (e.g. 0.5 NEAR) remains after
+    /// minting, on top of the deposits reserved for mint jobs.
+    fn assert_free_storage_stake(&self) {
+        let used_storage_stake: Balance = env::storage_usage() as u128 * env::storage_byte_cost();
+        let free_storage_stake: Balance = env::account_balance()
+            .saturating_sub(used_storage_stake)
+            .saturating_sub(self.reserved_mint_job_deposit);
+        near_assert!(
+            free_storage_stake > MINIMUM_FREE_STORAGE_STAKE,
+            "A minimum of {} yoctoNEAR is required as free contract balance to allow updates (currently: {})",
+            MINIMUM_FREE_STORAGE_STAKE,
+            free_storage_stake
+        );
+    }
+
+    /// Reserve `deposit` for the tokens of the unfinished mint job with
+    /// `job_id`.
+    pub(crate) fn reserve_mint_job_deposit(
+        &mut self,
+        job_id: u64,
+        deposit: Balance,
+    ) {
+        self.mint_job_deposits.insert(&job_id, &deposit);
+        self.reserved_mint_job_deposit += deposit;
+        self.num_pending_mint_jobs += 1;
+    }
+
+    /// Release the share of `num_to_mint` out of the `remaining` tokens from
+    /// the deposit reserved for the mint job with `job_id`. Once no tokens
+    /// remain, the rest of the deposit is released and the job is no longer
+    /// pending.
+    fn release_mint_job_deposit(
+        &mut self,
+        job_id: u64,
+        num_to_mint: u64,
+        remaining: u64,
+    ) {
+        let reserved = self.mint_job_deposits.get(&job_id).unwrap_or(0);
+        let released = if num_to_mint < remaining {
+            let released = reserved / remaining as u128 * num_to_mint as u128;
+            self.mint_job_deposits
+                .insert(&job_id, &(reserved - released));
+            released
+        } else {
+            self.mint_job_deposits.remove(&job_id);
+            self.num_pending_mint_jobs = self.num_pending_mint_jobs.saturating_sub(1);
+            reserved
+        };
+        self.reserved_mint_job_deposit = self.reserved_mint_job_deposit.saturating_sub(released);
+    }
+
+    /// Validate the arguments of a mint that are independent of the
+    /// metadata, returning the royalty and split ownership of the tokens.
+    fn check_mint_args(
+        &self,
+        num_to_mint: u64,
+        royalty_args: Option<RoyaltyArgs>,
+        split_owners: Option<SplitBetweenUnparsed>,
+    ) -> (Option<Royalty>, Option<SplitOwners>) {
+        self.assert_not_migrating();
+        near_assert!(!self.minting_paused, "Minting is paused on this store");
+        near_assert!(num_to_mint > 0, "No tokens to mint");
+        near_assert!(
+            self.tokens_minted.checked_add(num_to_mint).is_some(),
+            "Minting {} tokens would overflow the token IDs",
+            num_to_mint
+        );
+
+        let checked_royalty = royalty_args
+            .map(Royalty::new)
+            .or_else(|| self.default_royalty.clone());
+        if let Some(royalty) = checked_royalty.as_ref() {
+            near_assert!(
+                royalty.percentage.numerator <= self.max_royalty_percentage,
+                "Royalties on this store must not exceed {} of 10000",
+                self.max_royalty_percentage
+            );
+        }
+
+        let roy_len = checked_royalty
+            .as_ref()
+            .map(|royalty| royalty.split_between.len() as u32)
+            .unwrap_or(0);
+        let split_len = split_owners
+            .as_ref()
+            .map(|pre_split| {
+                let len = pre_split.len();
+                len as u32
+            })
+            // if there is no split map, there still is an owner, thus default to 1
+            .unwrap_or(1);
+        near_assert!(
+            roy_len + split_len <= MAX_LEN_PAYOUT,
+            "Number of payout addresses may not exceed {}",
+            MAX_LEN_PAYOUT
+        );
+        (checked_royalty, split_owners.map(SplitOwners::new))
+    }
+
+    /// Create the tokens with IDs `first_token_id` to `first_token_id +
+    /// num_to_mint - 1`, index them and emit the mint event. Metadata and
+    /// royalty must already be stored, and the token IDs must not be in use.
+    #[allow(clippy::too_many_arguments)]
+    fn mint_tokens(
+        &mut self,
+        minter_id: &AccountId,
+        owner_id: &AccountId,
+        metadata_id: u64,
+        royalty_id: Option<u64>,
+        split_owners: &Option<SplitOwners>,
+        first_token_id: u64,
+        num_to_mint: u64,
+    ) {
+        let mut owned_set = self.get_or_make_new_owner_set(owner_id);
+        let mut minted_set = self.get_or_make_new_minter_set(minter_id);
+        let mut edition_set = self.get_or_make_new_metadata_set(metadata_id);
+
+        // Mint em up hot n fresh with a side of vegan bacon
+        (0..num_to_mint).for_each(|i| {
+            let token_id = first_token_id + i;
+            let token = Token::new(
+                owner_id.clone(),
+                token_id,
+                metadata_id,
+                royalty_id,
+                split_owners.clone(),
+                minter_id.clone(),
+            );
+            owned_set.insert(&token_id, &());
+            minted_set.insert(&token_id, &());
+            edition_set.insert(&token_id, &());
+            self.tokens.insert(&token_id, &token);
+            self.live_tokens.insert(&token_id, &());
+        });
+        self.tokens_per_owner.insert(owner_id, &owned_set);
+        self.holders.insert(owner_id, &());
+        self.tokens_per_minter.insert(minter_id, &minted_set);
+        self.tokens_per_metadata.insert(&metadata_id, &edition_set);
+        self.update_edition_holders(metadata_id, None, Some(owner_id), num_to_mint);
+
+        let (_, metadata) = self.token_metadata.get(&metadata_id).unwrap();
+        let royalty = royalty_id.map(|id| self.token_royalty.get(&id).unwrap().1);
+        log_nft_batch_mint(
+            first_token_id,
+            first_token_id + num_to_mint - 1,
+            minter_id.as_ref(),
+            owner_id.as_ref(),
+            &royalty,
+            split_owners,
+            &metadata.reference,
+            &metadata.extra,
+        );
+    }
+
+    /// Get the storage in bytes that minting `num_to_mint` tokens creates,
+    /// computed from the Borsh-serialized records.
+    pub(crate) fn storage_usage_to_mint(
         &self,
-        num_tokens: u64,
-        metadata_storage: StorageUsage,
-        num_royalties: u32,
-        num_splits: u32,
-    ) -> near_sdk::Balance {
-        // create an entry in tokens_per_owner
-        self.storage_costs.common
-            // create a metadata record
-            + metadata_storage as u128 * self.storage_costs.storage_price_per_byte
-            // create a royalty record
-            + num_royalties as u128 * self.storage_costs.common
-            // create n tokens each with splits stored on-token
-            + num_tokens as u128 * (self.storage_costs.token + num_splits as u128 * self.storage_costs.common)
+        minter_id: &AccountId,
+        owner_id: &AccountId,
+        metadata: &TokenMetadata,
+        num_to_mint: u64,
+        royalty: Option<&Royalty>,
+        split_owners: Option<&SplitOwners>,
+    ) -> StorageUsage {
+        let record = |key_len: usize, value_len: usize| {
+            (key_len + value_len) as StorageUsage + storage_bytes::RECORD
+        };
+        // single byte collection prefix plus the u64 lookup ID
+        let id_key_len = 1 + 8;
+        let copies = num_to_mint;
+
+        // a metadata record and a royalty record, shared by all tokens, and
+        // their content hashes, unless the same content has been stored before
+        let hash_record = record(1 + 32, 8);
+        let new_metadata = !self
+            .metadata_ids_by_hash
+            .contains_key(&metadata_hash(metadata));
+        let mut usage = 0;
+        if new_metadata {
+            usage += record(id_key_len, (copies, metadata).try_to_vec().unwrap().len());
+            usage += hash_record;
+        }
+        if let Some(royalty) = royalty {
+            if !self
+                .royalty_ids_by_hash
+                .contains_key(&royalty_hash(royalty))
+            {
+                usage += record(id_key_len, (copies, royalty).try_to_vec().unwrap().len());
+                usage += hash_record;
+            }
+        }
+
+        // a record per token, with splits stored on-token
+        let lookup_id = self.tokens_minted;
+        let token = Token::new(
+            owner_id.clone(),
+            lookup_id,
+            lookup_id,
+            royalty.map(|_| lookup_id),
+            split_owners.cloned(),
+            minter_id.clone(),
+        );
+        usage += num_to_mint * record(id_key_len, token.try_to_vec().unwrap().len());
+
+        // a node and a value record per token in each tree that indexes
+        // tokens, keyed by a prefix of `prefix_len` bytes, where each new node
+        // adds a child ID to its parent (except for the root node of a tree
+        // that has been empty)
+        let tree_records = |prefix_len: usize, tree_len: u64| {
+            let node_key_len = prefix_len + 1 + 8;
+            num_to_mint
+                * (record(node_key_len, 0) + storage_bytes::TREE_NODE + record(node_key_len, 0))
+                + 8 * (num_to_mint - (tree_len == 0) as u64)
+        };
+        let index_len = |tree: Option<TreeMap<u64, ()>>| tree.map(|tree| tree.len()).unwrap_or(0);
+        usage += tree_records(1, self.live_tokens.len());
+        let metadata_id = if new_metadata {
+            None
+        } else {
+            self.metadata_ids_by_hash.get(&metadata_hash(metadata))
+        };
+        // a count of restocked copies, unless the edition has been restocked
+        // before
+        if let Some(metadata_id) = metadata_id {
+            if !self.restocked_copies.contains_key(&metadata_id) {
+                usage += record(id_key_len, 8);
+            }
+        }
+        let edition_len = metadata_id
+            .map(|metadata_id| index_len(self.tokens_per_metadata.get(&metadata_id)))
+            .unwrap_or(0);
+        usage += tree_records(1 + 8, edition_len);
+        usage += tree_records(
+            1 + minter_id.as_bytes().len(),
+            index_len(self.tokens_per_minter.get(minter_id)),
+        );
+        usage += tree_records(
+            1 + owner_id.as_bytes().len(),
+            index_len(self.tokens_per_owner.get(owner_id)),
+        );
+
+        // entries in `tokens_per_metadata`, `tokens_per_owner` and
+        // `tokens_per_minter` if not yet present
+        if new_metadata {
+            let edition_set = self.get_or_make_new_metadata_set(lookup_id);
+            usage += record(id_key_len, edition_set.try_to_vec().unwrap().len());
+        }
+        if self.tokens_per_owner.get(owner_id).is_none() {
+            let owner_set = self.get_or_make_new_owner_set(owner_id);
+            let owner_len = owner_id.try_to_vec().unwrap().len();
+            usage += record(1 + owner_len, owner_set.try_to_vec().unwrap().len());
+            // a node and a value record keyed by the owner in `holders`, where
+            // the node adds a child ID to its parent unless there are no
+            // holders yet
+            usage += record(1 + 1 + 8, owner_len) + storage_bytes::TREE_NODE - 8;
+            usage += record(1 + 1 + owner_len, 0) + 8 * (self.holders.len() > 0) as u64;
+        }
+        if self.tokens_per_minter.get(minter_id).is_none() {
+            let minter_set = self.get_or_make_new_minter_set(minter_id);
+            usage += record(
+                1 + minter_id.try_to_vec().unwrap().len(),
+                minter_set.try_to_vec().unwrap().len(),
+            );
+        }
+
+        // a count for the owner among the holders of the edition, with a node
+        // and a value record keyed by the owner, and an entry in
+        // `edition_holders` if the edition has no holders yet
+        let holder_counts =
+            metadata_id.and_then(|metadata_id| self.edition_holders.get(&metadata_id));
+        let holds_edition = holder_counts
+            .as_ref()
+            .map(|holder_counts| holder_counts.contains_key(owner_id))
+            .unwrap_or(false);
+        if !holds_edition {
+            let owner_len = owner_id.try_to_vec().unwrap().len();
+            let node_key_len = 1 + 8 + 1 + 8;
+            usage += record(node_key_len, owner_len) + storage_bytes::TREE_NODE - 8;
+            usage += record(1 + 8 + 1 + owner_len, 8);
+            match holder_counts {
+                Some(_) => usage += 8,
+                None => {
+                    let holder_counts = self.get_or_make_new_edition_holders(lookup_id);
+                    usage += record(id_key_len, holder_counts.try_to_vec().unwrap().len());
+                },
+            }
+        }
+        usage
+    }
+}
+
+impl MintbaseStore {
+    /// Store `num_to_mint` references to `metadata`. If the same metadata
+    /// has been stored before, its reference count is increased and the
+    /// copies are counted as restocked, leaving the stored metadata as it
+    /// is. Otherwise it is stored under `lookup_id`. Returns the ID of the
+    /// metadata.
+    fn insert_metadata(
+        &mut self,
+        lookup_id: u64,
+        metadata: TokenMetadata,
+        num_to_mint: u64,
+    ) -> u64 {
+        let hash = metadata_hash(&metadata);
+        match self.metadata_ids_by_hash.get(&hash) {
+            Some(metadata_id) => {
+                let (count, stored) = self.token_metadata.get(&metadata_id).unwrap();
+                self.token_metadata
+                    .insert(&metadata_id, &(add_copies(count, num_to_mint), stored));
+                let restocked = self.restocked_copies.get(&metadata_id).unwrap_or(0);
+                self.restocked_copies
+                    .insert(&metadata_id, &add_copies(restocked, num_to_mint));
+                metadata_id
+            },
+            None => {
+                self.token_metadata
+                    .insert(&lookup_id, &(num_to_mint, metadata));
+                self.metadata_ids_by_hash.insert(&hash, &lookup_id);
+                lookup_id
+            },
+        }
     }
+
+    /// Store `num_to_mint` references to `royalty`. If the same royalty has
+    /// been stored before, its reference count is increased, otherwise it is
+    /// stored under `lookup_id`. Returns the ID of the royalty.
+    fn insert_royalty(
+        &mut self,
+        lookup_id: u64,
+        royalty: Royalty,
+        num_to_mint: u64,
+    ) -> u64 {
+        let hash = royalty_hash(&royalty);
+        match self.royalty_ids_by_hash.get(&hash) {
+            Some(royalty_id) => {
+                let (count, stored) = self.token_royalty.get(&royalty_id).unwrap();
+                self.token_royalty
+                    .insert(&royalty_id, &(add_copies(count, num_to_mint), stored));
+                royalty_id
+            },
+            None => {
+                self.token_royalty
+                    .insert(&lookup_id, &(num_to_mint, royalty));
+                self.royalty_ids_by_hash.insert(&hash, &lookup_id);
+                lookup_id
+            },
+        }
+    }
+}
+
+/// Add `num_to_mint` to a count of copies, rejecting overflows.
+fn add_copies(
+    count: u64,
+    num_to_mint: u64,
+) -> u64 {
+    count
+        .checked_add(num_to_mint)
+        .unwrap_or_else(|| env::panic_str("Too many copies of this metadata or royalty"))
+}
+
+pub(crate) fn assert_valid_max_royalty_percentage(percentage: u32) {
+    near_assert!(
+        percentage <= ROYALTY_UPPER_LIMIT,
+        "Royalty cap cannot exceed {}",
+        ROYALTY_UPPER_LIMIT
+    );
 }
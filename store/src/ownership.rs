@@ -1,19 +1,30 @@
+use mintbase_deps::constants::{
+    gas,
+    StorageCostsJson,
+    NO_DEPOSIT,
+};
+use mintbase_deps::interfaces::ext_factory;
 use mintbase_deps::logging::{
     log_grant_minter,
     log_revoke_minter,
+    log_store_decommission,
     log_transfer_store,
 };
 use mintbase_deps::near_sdk::{
     self,
+    is_promise_success,
     near_bindgen,
     AccountId,
+    Promise,
 };
 use mintbase_deps::{
     assert_yocto_deposit,
+    near_assert,
     near_assert_eq,
     near_assert_ne,
 };
 
+use crate::upgrade::factory_account_id;
 use crate::*;
 
 #[near_bindgen]
@@ -38,6 +49,7 @@ impl MintbaseStore {
             new_owner
         );
         if !keep_old_minters {
+            self.assert_not_timelocked();
             for minter in self.minters.iter() {
                 log_revoke_minter(&minter);
             }
@@ -47,20 +59,31 @@ impl MintbaseStore {
         // add the new_owner to the minter set (insert does nothing if they already are a minter).
         self.minters.insert(&new_owner);
         log_transfer_store(&new_owner);
+        // keep the factory registry up to date
+        ext_factory::update_store_owner(
+            new_owner.clone(),
+            factory_account_id(),
+            NO_DEPOSIT,
+            gas::UPDATE_STORE_OWNER,
+        );
         self.owner_id = new_owner;
     }
 
     /// Owner of this `Store` may call to withdraw Near deposited onto
     /// contract for storage. Contract storage deposit must maintain a
     /// cushion of at least 50kB (0.5 Near) beyond that necessary for storage
-    /// usage.
+    /// usage. Deposits reserved for the tokens of unfinished mint jobs cannot
+    /// be withdrawn.
     ///
     /// Only the store owner may call this function.
     #[payable]
     pub fn withdraw_excess_storage_deposits(&mut self) {
         self.assert_store_owner();
         let unused_deposit: u128 = env::account_balance()
-            - env::storage_usage() as u128 * self.storage_costs.storage_price_per_byte;
+            .saturating_sub(
+                env::storage_usage() as u128 * self.storage_costs.storage_price_per_byte,
+            )
+            .saturating_sub(self.reserved_mint_job_deposit);
         if unused_deposit > storage_stake::CUSHION {
             near_sdk::Promise::new(self.owner_id.clone())
                 .transfer(unused_deposit - storage_stake::CUSHION);
@@ -73,25 +96,109 @@ impl MintbaseStore {
         }
     }
 
+    /// Retire this `Store` in the factory registry and delete its account,
+    /// sending the remaining balance to the store owner. Only possible if
+    /// all tokens have been burned or moved to other contracts. Minting is
+    /// paused while the factory is retiring the store, and the account is
+    /// only deleted once that succeeded. Retiring is idempotent in the
+    /// factory, so decommissioning may be retried if deleting the account
+    /// failed.
+    ///
+    /// Only the store owner may call this function.
+    #[payable]
+    pub fn decommission_store(&mut self) -> Promise {
+        self.assert_store_owner();
+        self.assert_no_remaining_tokens();
+
+        let minting_paused = self.minting_paused;
+        self.minting_paused = true;
+        ext_factory::retire_store(factory_account_id(), NO_DEPOSIT, gas::RETIRE_STORE).then(
+            store_self::on_retire_store(
+                self.owner_id.clone(),
+                minting_paused,
+                env::current_account_id(),
+                NO_DEPOSIT,
+                gas::ON_RETIRE_STORE_CALLBACK,
+            ),
+        )
+    }
+
     /// The Near Storage price per byte has changed in the past, and may
     /// change in the future. This method may never be used.
     ///
-    /// Only the store owner may call this function.
+    /// Only the store owner may call this function. If the store is
+    /// timelocked, the change needs to be scheduled instead.
     #[payable]
     pub fn set_storage_price_per_byte(
         &mut self,
         new_price: U128,
     ) {
         self.assert_store_owner();
+        self.assert_not_timelocked();
         self.storage_costs = StorageCosts::new(new_price.into())
     }
 
     // -------------------------- view methods -----------------------------
-    // TODO: get_owner
-    // TODO: get_storage_price_per_byte
+
+    /// The owner of this `Store`.
+    pub fn get_owner(&self) -> AccountId {
+        self.owner_id.clone()
+    }
+
+    /// The Near-denominated price per byte of storage on this `Store`.
+    pub fn get_storage_price_per_byte(&self) -> U128 {
+        self.storage_costs.storage_price_per_byte.into()
+    }
+
+    /// The storage costs on this `Store`, as derived from the storage price
+    /// per byte.
+    pub fn get_storage_costs(&self) -> StorageCostsJson {
+        (&self.storage_costs).into()
+    }
+
     // -------------------------- private methods --------------------------
+
+    /// Delete the account of this `Store` after the factory has retired it,
+    /// sending the remaining balance to `beneficiary_id`. If retiring failed,
+    /// minting is restored to `minting_paused`, and decommissioning may be
+    /// retried. Returns whether the account is being deleted.
+    #[private]
+    pub fn on_retire_store(
+        &mut self,
+        beneficiary_id: AccountId,
+        minting_paused: bool,
+    ) -> bool {
+        if !is_promise_success() {
+            env::log_str("Failed to retire this store in the factory registry");
+            self.minting_paused = minting_paused;
+            return false;
+        }
+        self.assert_no_remaining_tokens();
+        log_store_decommission(&env::current_account_id(), &beneficiary_id);
+        Promise::new(env::current_account_id()).delete_account(beneficiary_id);
+        true
+    }
+
     // -------------------------- internal methods -------------------------
 
+    /// Panics if any tokens are left on this `Store`, or remain to be minted
+    /// by mint jobs. The indexes that this relies on must not be in the
+    /// middle of a backfill.
+    fn assert_no_remaining_tokens(&self) {
+        self.assert_not_migrating();
+        let remaining_tokens = self.live_tokens.len();
+        near_assert!(
+            remaining_tokens == 0,
+            "Cannot decommission store with {} remaining tokens",
+            remaining_tokens
+        );
+        near_assert!(
+            self.num_pending_mint_jobs == 0,
+            "Cannot decommission store with {} unfinished mint jobs",
+            self.num_pending_mint_jobs
+        );
+    }
+
     /// Validate the caller of this method matches the owner of this `Store`.
     pub(crate) fn assert_store_owner(&self) {
         assert_yocto_deposit!();
@@ -43,7 +43,13 @@ impl MintbaseStore {
     ) -> Payout {
         assert_yocto_deposit!();
         let payout = self.nft_payout(token_id, balance, max_len_payout);
-        self.nft_transfer(receiver_id, token_id, Some(approval_id), None);
+        self.nft_transfer_internal(
+            receiver_id,
+            token_id.into(),
+            Some(approval_id),
+            None,
+            Some(balance.0),
+        );
         payout
     }
 
@@ -91,6 +97,7 @@ impl MintbaseStore {
         token_ids: Vec<U64>,
         split_between: SplitBetweenUnparsed,
     ) {
+        self.assert_not_migrating();
         near_assert!(!token_ids.is_empty(), "Requires token IDs");
         // near_assert!(
         //     split_between.len() >= 2,
@@ -0,0 +1,234 @@
+use mintbase_deps::common::time::{
+    now,
+    NearTime,
+};
+use mintbase_deps::common::{
+    PendingStoreChange,
+    Royalty,
+    StoreChange,
+};
+use mintbase_deps::constants::MAX_ADMIN_DELAY;
+use mintbase_deps::logging::{
+    log_cancel_store_change,
+    log_execute_store_change,
+    log_schedule_store_change,
+    log_set_admin_delay,
+};
+use mintbase_deps::near_sdk::json_types::{
+    Base58CryptoHash,
+    U64,
+};
+use mintbase_deps::near_sdk::{
+    self,
+    env,
+    near_bindgen,
+};
+use mintbase_deps::{
+    near_assert,
+    near_panic,
+};
+
+use crate::*;
+
+#[near_bindgen]
+impl MintbaseStore {
+    // -------------------------- change methods ---------------------------
+
+    /// Schedule an administrative change to this `Store`. The change may be
+    /// executed via `execute_store_change` once the admin delay of this
+    /// `Store` has passed, and may be cancelled before that.
+    ///
+    /// Only the store owner may call this function.
+    #[payable]
+    pub fn schedule_store_change(
+        &mut self,
+        change: StoreChange,
+    ) -> U64 {
+        self.assert_store_owner();
+        match change {
+            StoreChange::SetAdminDelay(delay) => assert_valid_admin_delay(delay.0),
+            StoreChange::SetMaxRoyaltyPercentage(percentage) => {
+                minting::assert_valid_max_royalty_percentage(percentage)
+            },
+            _ => {},
+        }
+
+        let scheduled_at = now();
+        let pending = PendingStoreChange {
+            id: self.num_scheduled_changes.into(),
+            change,
+            executable_at: NearTime(scheduled_at.0 + self.admin_delay),
+            scheduled_at,
+        };
+        self.num_scheduled_changes += 1;
+        self.pending_changes.insert(&pending.id.0, &pending);
+        log_schedule_store_change(&pending);
+        pending.id
+    }
+
+    /// Execute a previously scheduled administrative change. Panics if the
+    /// admin delay has not yet passed.
+    ///
+    /// Only the store owner may call this function.
+    #[payable]
+    pub fn execute_store_change(
+        &mut self,
+        change_id: U64,
+    ) {
+        self.assert_store_owner();
+        let pending = self.get_pending_change_internal(change_id.0);
+        near_assert!(
+            !pending.executable_at.is_before_timeout(),
+            "Change {} cannot be executed before {}",
+            change_id.0,
+            pending.executable_at.0
+        );
+
+        self.pending_changes.remove(&change_id.0);
+        self.apply_store_change(pending.change.clone());
+        log_execute_store_change(&pending);
+    }
+
+    /// Cancel a previously scheduled administrative change.
+    ///
+    /// Only the store owner may call this function.
+    #[payable]
+    pub fn cancel_store_change(
+        &mut self,
+        change_id: U64,
+    ) {
+        self.assert_store_owner();
+        if self.pending_changes.remove(&change_id.0).is_none() {
+            near_panic!("No pending change with ID {}", change_id.0);
+        }
+        log_cancel_store_change(change_id.0);
+    }
+
+    /// Set the delay in nanoseconds between scheduling and executing
+    /// administrative changes. Increasing the delay takes effect immediately,
+    /// decreasing it needs to be scheduled if the store is timelocked.
+    ///
+    /// Only the store owner may call this function.
+    #[payable]
+    pub fn set_admin_delay(
+        &mut self,
+        delay: U64,
+    ) {
+        self.assert_store_owner();
+        assert_valid_admin_delay(delay.0);
+        if delay.0 < self.admin_delay {
+            self.assert_not_timelocked();
+        }
+        self.admin_delay = delay.0;
+        log_set_admin_delay(delay.0);
+    }
+
+    // -------------------------- view methods -----------------------------
+
+    /// The delay in nanoseconds between scheduling and executing
+    /// administrative changes.
+    pub fn get_admin_delay(&self) -> U64 {
+        self.admin_delay.into()
+    }
+
+    /// Lists all administrative changes that have been scheduled, but not
+    /// yet executed or cancelled.
+    pub fn list_pending_store_changes(&self) -> Vec<PendingStoreChange> {
+        self.pending_changes.values().collect()
+    }
+
+    // -------------------------- private methods --------------------------
+    // -------------------------- internal methods -------------------------
+
+    /// Panics if administrative changes to this `Store` need to be scheduled.
+    pub(crate) fn assert_not_timelocked(&self) {
+        near_assert!(
+            self.admin_delay == 0,
+            "Store changes are timelocked, use `schedule_store_change` instead"
+        );
+    }
+
+    /// Remove the scheduled upgrade to the code with `code_hash`, panicking
+    /// if there is none or if its delay has not yet passed.
+    pub(crate) fn take_scheduled_upgrade(
+        &mut self,
+        code_hash: Base58CryptoHash,
+    ) {
+        let scheduled: Vec<PendingStoreChange> = self
+            .pending_changes
+            .values()
+            .filter(|pending| {
+                matches!(
+                    &pending.change,
+                    StoreChange::Upgrade { code_hash: hash } if *hash == code_hash
+                )
+            })
+            .collect();
+        let pending = match scheduled
+            .iter()
+            .find(|pending| !pending.executable_at.is_before_timeout())
+        {
+            Some(pending) => pending,
+            None => match scheduled.first() {
+                Some(pending) => near_panic!(
+                    "Change {} cannot be executed before {}",
+                    pending.id.0,
+                    pending.executable_at.0
+                ),
+                None => near_panic!(
+                    "Upgrades are timelocked, use `schedule_store_change` with the code hash first"
+                ),
+            },
+        };
+        self.pending_changes.remove(&pending.id.0);
+        log_execute_store_change(pending);
+    }
+
+    fn get_pending_change_internal(
+        &self,
+        change_id: u64,
+    ) -> PendingStoreChange {
+        self.pending_changes
+            .get(&change_id)
+            .unwrap_or_else(|| near_panic!("No pending change with ID {}", change_id))
+    }
+
+    fn apply_store_change(
+        &mut self,
+        change: StoreChange,
+    ) {
+        match change {
+            StoreChange::SetAllowMoves(state) => self.allow_moves = state,
+            StoreChange::SetStoragePricePerByte(new_price) => {
+                self.storage_costs = StorageCosts::new(new_price.into())
+            },
+            StoreChange::RevokeMinter(account_id) => self.revoke_minter_internal(&account_id),
+            StoreChange::SetAdminDelay(delay) => {
+                self.admin_delay = delay.0;
+                log_set_admin_delay(delay.0);
+            },
+            StoreChange::SetMaxRoyaltyPercentage(percentage) => {
+                self.max_royalty_percentage = percentage
+            },
+            StoreChange::SetMintingPaused(paused) => self.minting_paused = paused,
+            StoreChange::SetDefaultRoyalty(royalty_args) => {
+                self.default_royalty = royalty_args.map(Royalty::new)
+            },
+            StoreChange::Upgrade { .. } => {
+                near_panic!("Upgrades are executed via `upgrade_store` on the factory")
+            },
+            StoreChange::SetAllowFactoryUpgrades(allow) => self.allow_factory_upgrades = allow,
+            StoreChange::SetAllowFactoryEmergencyActions(allow) => {
+                self.allow_factory_emergency_actions = allow
+            },
+        }
+    }
+}
+
+fn assert_valid_admin_delay(delay: u64) {
+    near_assert!(
+        delay <= MAX_ADMIN_DELAY,
+        "Admin delay cannot exceed {} nanoseconds",
+        MAX_ADMIN_DELAY
+    );
+}
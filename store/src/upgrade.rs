@@ -0,0 +1,146 @@
+use mintbase_deps::common::StoreUpgradeArgs;
+use mintbase_deps::constants::{
+    gas,
+    NO_DEPOSIT,
+};
+use mintbase_deps::logging::log_emergency_pause_minting;
+use mintbase_deps::near_sdk::{
+    self,
+    env,
+    near_bindgen,
+    AccountId,
+    CryptoHash,
+    Promise,
+};
+use mintbase_deps::serde_json::json;
+use mintbase_deps::{
+    near_assert,
+    near_assert_eq,
+};
+
+use crate::*;
+
+#[near_bindgen]
+impl MintbaseStore {
+    // -------------------------- change methods ---------------------------
+
+    /// Allow or disallow the owner of the `Factory` to upgrade the code of
+    /// this `Store`. Upgrades are restricted to code that has been uploaded
+    /// to the `Factory`.
+    ///
+    /// Only the store owner may call this function. If the store is
+    /// timelocked, the change needs to be scheduled instead.
+    #[payable]
+    pub fn set_allow_factory_upgrades(
+        &mut self,
+        allow: bool,
+    ) {
+        self.assert_store_owner();
+        self.assert_not_timelocked();
+        self.allow_factory_upgrades = allow;
+    }
+
+    /// Allow or disallow the owner of the `Factory` to trigger emergency
+    /// actions on this `Store`, such as pausing minting.
+    ///
+    /// Only the store owner may call this function. If the store is
+    /// timelocked, the change needs to be scheduled instead.
+    #[payable]
+    pub fn set_allow_factory_emergency_actions(
+        &mut self,
+        allow: bool,
+    ) {
+        self.assert_store_owner();
+        self.assert_not_timelocked();
+        self.allow_factory_emergency_actions = allow;
+    }
+
+    /// Pause minting on this `Store`. This has to be triggered via
+    /// `emergency_pause_store` on the `Factory`, and requires the store
+    /// owner to allow emergency actions by the factory. The store owner may
+    /// resume minting via `set_minting_paused`, or by scheduling
+    /// `StoreChange::SetMintingPaused` if the store is timelocked.
+    pub fn emergency_pause_minting(&mut self) {
+        near_assert_eq!(
+            env::predecessor_account_id(),
+            factory_account_id(),
+            "Emergency actions can only be triggered via the factory"
+        );
+        near_assert!(
+            self.allow_factory_emergency_actions,
+            "The store owner does not allow emergency actions by the factory"
+        );
+        self.minting_paused = true;
+        log_emergency_pause_minting(&env::current_account_id(), &factory_account_id());
+    }
+
+    /// Deploy new code on this `Store` and migrate the state to the layout
+    /// of the new code. The upgrade has to be triggered via `upgrade_store`
+    /// on the `Factory`, which provides the code.
+    ///
+    /// The upgrade must have been requested either by the store owner, or by
+    /// the owner of the `Factory` if the store owner allowed that. If the
+    /// store is timelocked, the store owner must have scheduled a
+    /// `StoreChange::Upgrade` with the hash of the code, and its delay must
+    /// have passed.
+    pub fn upgrade(
+        &mut self,
+        #[serializer(borsh)] args: StoreUpgradeArgs,
+    ) -> Promise {
+        near_assert_eq!(
+            env::predecessor_account_id(),
+            factory_account_id(),
+            "Upgrades can only be triggered via the factory"
+        );
+        near_assert!(
+            args.requested_by == self.owner_id
+                || (args.by_factory_owner && self.allow_factory_upgrades),
+            "{} is not allowed to upgrade this store",
+            args.requested_by
+        );
+        if self.admin_delay > 0 {
+            let code_hash: CryptoHash = env::sha256(&args.code).try_into().unwrap();
+            self.take_scheduled_upgrade(code_hash.into());
+        }
+
+        let migrate_args = json!({ "metadata": self.metadata })
+            .to_string()
+            .into_bytes();
+        Promise::new(env::current_account_id())
+            .deploy_contract(args.code)
+            .function_call(
+                "migrate".to_string(),
+                migrate_args,
+                NO_DEPOSIT,
+                gas::MIGRATE_STORE,
+            )
+    }
+
+    // -------------------------- view methods -----------------------------
+
+    /// Whether the owner of the `Factory` may upgrade the code of this
+    /// `Store`.
+    pub fn get_allow_factory_upgrades(&self) -> bool {
+        self.allow_factory_upgrades
+    }
+
+    /// Whether the owner of the `Factory` may trigger emergency actions on
+    /// this `Store`.
+    pub fn get_allow_factory_emergency_actions(&self) -> bool {
+        self.allow_factory_emergency_actions
+    }
+
+    // -------------------------- private methods --------------------------
+    // -------------------------- internal methods -------------------------
+}
+
+/// Stores are deployed as subaccounts of the `Factory`, so the `Factory` is
+/// the parent account.
+pub(crate) fn factory_account_id() -> AccountId {
+    let store_id = env::current_account_id();
+    let (_, factory_id) = store_id
+        .as_str()
+        .split_once('.')
+        .expect("Store is not a subaccount");
+    AccountId::new_unchecked(factory_id.to_string())
+}
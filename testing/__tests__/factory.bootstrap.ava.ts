@@ -0,0 +1,129 @@
+import {
+  FACTORY_WORKSPACE,
+  assertContractPanics,
+  failPromiseRejection,
+  NEAR,
+  Tgas,
+} from "./test-utils";
+
+FACTORY_WORKSPACE.test(
+  "factory::bootstrap",
+  async (test, { alice, bob, carol, factory }) => {
+    await alice
+      .call(
+        factory,
+        "create_store",
+        {
+          owner_id: alice.accountId,
+          metadata: { spec: "nft-1.0.0", name: "alice", symbol: "ALICE" },
+          bootstrap: {
+            minters: [bob.accountId],
+            settings: { minting_paused: false, max_royalty_percentage: 1000 },
+            default_royalty: {
+              split_between: { [carol.accountId]: 10000 },
+              percentage: 500,
+            },
+            initial_mint: {
+              metadata: { title: "first" },
+              num_to_mint: 2,
+            },
+          },
+        },
+        { attachedDeposit: NEAR(8), gas: Tgas(300) }
+      )
+      .catch(failPromiseRejection(test, "bootstrapping store"));
+    const store = factory.getFullAccount(`alice.${factory.accountId}`);
+
+    test.deepEqual(
+      ((await store.view("list_minters")) as string[]).sort(),
+      [alice.accountId, bob.accountId].sort()
+    );
+    test.like(await store.view("get_store_settings"), {
+      allow_moves: true,
+      minting_paused: false,
+      max_royalty_percentage: 1000,
+    });
+    test.like(await store.view("nft_token", { token_id: "1" }), {
+      owner_id: alice.accountId,
+      metadata: { title: "first" },
+    });
+    test.like(await store.view("get_token_royalty", { token_id: "1" }), {
+      percentage: { numerator: 500 },
+    });
+
+    await assertContractPanics(test, [
+      [
+        async () => {
+          await bob.call(
+            store,
+            "nft_batch_mint",
+            {
+              owner_id: bob.accountId,
+              metadata: {},
+              num_to_mint: 1,
+              royalty_args: {
+                split_between: { [carol.accountId]: 10000 },
+                percentage: 2000,
+              },
+            },
+            { attachedDeposit: "1" }
+          );
+        },
+        "Royalties on this store must not exceed 1000 of 10000",
+        "Minting above the royalty cap",
+      ],
+    ]);
+
+    await alice
+      .call(store, "set_minting_paused", { paused: true }, { attachedDeposit: "1" })
+      .catch(failPromiseRejection(test, "pausing minting"));
+
+    await assertContractPanics(test, [
+      [
+        async () => {
+          await bob.call(
+            store,
+            "nft_batch_mint",
+            { owner_id: bob.accountId, metadata: {}, num_to_mint: 1 },
+            { attachedDeposit: "1" }
+          );
+        },
+        "Minting is paused on this store",
+        "Minting while paused",
+      ],
+    ]);
+
+    // if the initial mint fails, the forwarded deposit is refunded
+    const aliceBalance = await alice.availableBalance();
+    await alice
+      .call(
+        factory,
+        "create_store",
+        {
+          owner_id: alice.accountId,
+          metadata: { spec: "nft-1.0.0", name: "alice2", symbol: "ALICE" },
+          bootstrap: {
+            settings: { max_royalty_percentage: 1000 },
+            initial_mint: {
+              metadata: {},
+              num_to_mint: 1,
+              royalty_args: {
+                split_between: { [carol.accountId]: 10000 },
+                percentage: 2000,
+              },
+            },
+          },
+        },
+        { attachedDeposit: NEAR(8), gas: Tgas(300) }
+      )
+      .catch(failPromiseRejection(test, "failing bootstrap"));
+    test.false(
+      await factory.getFullAccount(`alice2.${factory.accountId}`).exists()
+    );
+    // only the store cost is kept, the remaining 2.5N are refunded
+    test.true(
+      aliceBalance.sub(await alice.availableBalance()).lt(NEAR(6)),
+      "Forwarded deposit was not refunded"
+    );
+  }
+);
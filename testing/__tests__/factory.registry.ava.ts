@@ -0,0 +1,352 @@
+import { BN, TransactionResult } from "near-workspaces-ava";
+import {
+  FACTORY_WORKSPACE,
+  STORE_WORKSPACE,
+  assertContractPanics,
+  assertEventLogs,
+  batchMint,
+  deployStore,
+  DEPLOY_STORE_GAS,
+  DEPLOY_STORE_RENT,
+  failPromiseRejection,
+  NEAR,
+} from "./test-utils";
+
+STORE_WORKSPACE.test(
+  "factory::registry",
+  async (test, { alice, bob, factory, store }) => {
+    await deployStore({ factory, owner: bob, name: "bob" });
+
+    test.is(await factory.view("get_num_stores"), "2");
+    test.like(await factory.view("get_store_info", { name: "alice" }), {
+      store_id: store.accountId,
+      owner_id: alice.accountId,
+      code_version: 0,
+      template_id: null,
+      metadata: { name: "alice" },
+    });
+    test.is(await factory.view("get_store_info", { name: "carol" }), null);
+
+    const stores: any[] = await factory.view("get_stores", {});
+    test.deepEqual(
+      stores.map((s) => s.metadata.name),
+      ["alice", "bob"]
+    );
+    const page: any[] = await factory.view("get_stores", {
+      from_index: "1",
+      limit: 1,
+    });
+    test.deepEqual(
+      page.map((s) => s.metadata.name),
+      ["bob"]
+    );
+
+    // name availability
+    test.false(await factory.view("check_store_name_available", { name: "alice" }));
+    test.false(await factory.view("check_store_name_available", { name: "market" }));
+    test.false(await factory.view("check_store_name_available", { name: "a.b" }));
+    test.true(await factory.view("check_store_name_available", { name: "carol" }));
+
+    // owner index follows ownership transfers
+    await alice
+      .call(
+        store,
+        "transfer_store_ownership",
+        { new_owner: bob.accountId, keep_old_minters: true },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "transferring store ownership"));
+    test.deepEqual(
+      await factory.view("get_stores_by_owner", { owner_id: alice.accountId }),
+      []
+    );
+    const bobStores: any[] = await factory.view("get_stores_by_owner", {
+      owner_id: bob.accountId,
+    });
+    test.deepEqual(
+      bobStores.map((s) => s.metadata.name).sort(),
+      ["alice", "bob"]
+    );
+  }
+);
+
+FACTORY_WORKSPACE.test(
+  "factory::name-policy",
+  async (test, { root, alice, factory }) => {
+    test.deepEqual((await factory.view("get_reserved_names")) as string[], [
+      "market",
+      "loan",
+    ]);
+
+    await root
+      .call(
+        factory,
+        "add_reserved_names",
+        { names: ["mintbase"] },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "reserving names"));
+    await root
+      .call(
+        factory,
+        "set_name_policy",
+        {
+          policy: {
+            min_length: 3,
+            max_length: 20,
+            reserved_patterns: ["*near*"],
+            premium_length: 4,
+            premium_fee: NEAR(1).toString(),
+          },
+        },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "setting name policy"));
+
+    const nameError = (name: string) =>
+      factory.view("get_store_name_error", { name });
+    test.is(await nameError("mintbase"), "Store name mintbase is reserved");
+    test.is(await nameError("nearnfts"), "Store name nearnfts is reserved");
+    test.is(
+      await nameError("ab"),
+      "Store name must have at least 3 characters"
+    );
+    test.is(
+      await nameError("abcdefghijklmnopqrstu"),
+      "Store name cannot have more than 20 characters"
+    );
+    test.is(await nameError("abcd"), null);
+
+    // premium names cost extra
+    const regular: string = await factory.view("get_minimum_attached_balance", {
+      store_name: "abcde",
+    });
+    const premium: string = await factory.view("get_minimum_attached_balance", {
+      store_name: "abcd",
+    });
+    test.is(
+      NEAR(1).toString(),
+      new BN(premium).sub(new BN(regular)).toString()
+    );
+
+    await assertContractPanics(test, [
+      [
+        async () => {
+          await alice.call(
+            factory,
+            "create_store",
+            {
+              owner_id: alice.accountId,
+              metadata: { spec: "nft-1.0.0", name: "near", symbol: "NEAR" },
+            },
+            { attachedDeposit: DEPLOY_STORE_RENT, gas: DEPLOY_STORE_GAS }
+          );
+        },
+        "Store name near is reserved",
+        "Creating store with reserved name",
+      ],
+      [
+        async () => {
+          await alice.call(
+            factory,
+            "set_name_policy",
+            {
+              policy: {
+                min_length: 1,
+                max_length: 64,
+                reserved_patterns: [],
+                premium_length: 0,
+                premium_fee: "0",
+              },
+            },
+            { attachedDeposit: "1" }
+          );
+        },
+        "Only contract owner can call this method",
+        "Non-owner tried to change the name policy",
+      ],
+    ]);
+  }
+);
+
+STORE_WORKSPACE.test(
+  "factory::decommission",
+  async (test, { alice, factory, store }) => {
+    await batchMint({ owner: alice, store, num_to_mint: 1 }).catch(
+      failPromiseRejection(test, "minting")
+    );
+
+    await assertContractPanics(test, [
+      [
+        async () => {
+          await alice.call(
+            store,
+            "decommission_store",
+            {},
+            { attachedDeposit: "1" }
+          );
+        },
+        "Cannot decommission store with 1 remaining tokens",
+        "Decommissioning store with remaining tokens",
+      ],
+    ]);
+
+    await alice
+      .call(store, "nft_batch_burn", { token_ids: ["0"] }, { attachedDeposit: "1" })
+      .catch(failPromiseRejection(test, "burning"));
+
+    // tokens that a mint job has yet to mint also remain
+    await alice
+      .call(
+        store,
+        "create_mint_job",
+        { owner_id: alice.accountId, metadata: {}, num_to_mint: 1 },
+        { attachedDeposit: NEAR(1) }
+      )
+      .catch(failPromiseRejection(test, "creating mint job"));
+    await assertContractPanics(test, [
+      [
+        async () => {
+          await alice.call(
+            store,
+            "decommission_store",
+            {},
+            { attachedDeposit: "1" }
+          );
+        },
+        "Cannot decommission store with 1 unfinished mint jobs",
+        "Decommissioning store with an unfinished mint job",
+      ],
+    ]);
+    await alice
+      .call(store, "continue_mint_job", { job_id: "0" })
+      .catch(failPromiseRejection(test, "continuing mint job"));
+    await alice
+      .call(store, "nft_batch_burn", { token_ids: ["1"] }, { attachedDeposit: "1" })
+      .catch(failPromiseRejection(test, "burning"));
+
+    const decommissionCall = await alice
+      .call_raw(store, "decommission_store", {}, { attachedDeposit: "1" })
+      .catch(failPromiseRejection(test, "decommissioning store"));
+    assertEventLogs(
+      test,
+      (decommissionCall as TransactionResult).logs,
+      [
+        {
+          standard: "mb_store",
+          version: "0.1.0",
+          event: "decommission",
+          data: { store_id: store.accountId, beneficiary_id: alice.accountId },
+        },
+      ],
+      "decommissioning store"
+    );
+
+    test.false(await store.exists());
+    test.not(
+      await factory.view("get_store_retired_at", { name: "alice" }),
+      null
+    );
+    test.deepEqual(
+      await factory.view("get_stores_by_owner", { owner_id: alice.accountId }),
+      []
+    );
+    // retired stores are hidden from the registry views
+    test.is(await factory.view("get_store_info", { name: "alice" }), null);
+    test.false(
+      ((await factory.view("get_stores", {})) as any[]).some(
+        (record) => record.store_id === store.accountId
+      )
+    );
+    test.false(
+      await factory.view("check_store_name_available", { name: "alice" })
+    );
+  }
+);
+
+FACTORY_WORKSPACE.test(
+  "factory::referral",
+  async (test, { root, alice, bob, carol, factory }) => {
+    await root
+      .call(
+        factory,
+        "set_referral_percentage",
+        { percentage: 2000 },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "setting referral percentage"));
+    await root
+      .call(
+        factory,
+        "set_mintbase_factory_fee",
+        { amount: NEAR(1).toString() },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "setting factory fee"));
+    await root
+      .call(
+        factory,
+        "add_eligible_referrers",
+        { account_ids: [bob.accountId] },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "adding eligible referrer"));
+    test.true(
+      await factory.view("check_is_eligible_referrer", {
+        account_id: bob.accountId,
+      })
+    );
+
+    const bobBalance = await bob.availableBalance();
+    const createCall = await alice
+      .call_raw(
+        factory,
+        "create_store",
+        {
+          owner_id: alice.accountId,
+          metadata: { spec: "nft-1.0.0", name: "alice", symbol: "ALICE" },
+          referrer_id: bob.accountId,
+        },
+        { attachedDeposit: DEPLOY_STORE_RENT, gas: DEPLOY_STORE_GAS }
+      )
+      .catch(failPromiseRejection(test, "creating store with referrer"));
+    // fee is 1N, 20% of which go to the referrer, while the overpayment of
+    // 7N deposit - 5.5N store cost - 1N fee goes to the treasury only
+    test.is(
+      (await bob.availableBalance()).sub(bobBalance).toString(),
+      NEAR(0.2).toString()
+    );
+    const deployLog = (createCall as TransactionResult).logs
+      .filter((log) => log.startsWith("EVENT_JSON:"))
+      .map((log) => JSON.parse(log.slice(11)))
+      .find((event) => event.event === "deploy");
+    test.like(deployLog, {
+      standard: "mb_store",
+      version: "0.2.0",
+      data: {
+        owner_id: alice.accountId,
+        referrer_id: bob.accountId,
+        referral_amount: NEAR(0.2).toString(),
+      },
+    });
+
+    await assertContractPanics(test, [
+      [
+        async () => {
+          await alice.call(
+            factory,
+            "create_store",
+            {
+              owner_id: alice.accountId,
+              metadata: { spec: "nft-1.0.0", name: "carol", symbol: "ALICE" },
+              referrer_id: carol.accountId,
+            },
+            { attachedDeposit: DEPLOY_STORE_RENT, gas: DEPLOY_STORE_GAS }
+          );
+        },
+        `${carol.accountId} is not an eligible referrer`,
+        "Creating store with ineligible referrer",
+      ],
+    ]);
+  }
+);
@@ -0,0 +1,116 @@
+import { TransactionResult } from "near-workspaces-ava";
+import {
+  FACTORY_WORKSPACE,
+  assertContractPanics,
+  assertEventLogs,
+  deployStore,
+  DEPLOY_STORE_GAS,
+  DEPLOY_STORE_RENT,
+  failPromiseRejection,
+  Tgas,
+} from "./test-utils";
+
+FACTORY_WORKSPACE.test(
+  "factory::security-modes",
+  async (test, { root, alice, bob, factory }) => {
+    test.is(await factory.view("get_default_security_mode"), "factory_calls");
+
+    // stores without admin key opt into factory calls by default
+    const aliceStore = await deployStore({ factory, owner: alice, name: "alice" });
+    test.is(
+      await factory.view("get_store_security_mode", { name: "alice" }),
+      "factory_calls"
+    );
+    test.like(await aliceStore.view("get_store_settings"), {
+      allow_factory_upgrades: true,
+      allow_factory_emergency_actions: true,
+    });
+
+    // creators may still choose the admin key
+    await bob
+      .call(
+        factory,
+        "create_store",
+        {
+          owner_id: bob.accountId,
+          metadata: { spec: "nft-1.0.0", name: "bob", symbol: "BOB" },
+          security_mode: "admin_key",
+        },
+        { attachedDeposit: DEPLOY_STORE_RENT, gas: DEPLOY_STORE_GAS }
+      )
+      .catch(failPromiseRejection(test, "creating store with admin key"));
+    const bobStore = factory.getFullAccount(`bob.${factory.accountId}`);
+    test.is(
+      await factory.view("get_store_security_mode", { name: "bob" }),
+      "admin_key"
+    );
+    test.like(await bobStore.view("get_store_settings"), {
+      allow_factory_upgrades: false,
+      allow_factory_emergency_actions: false,
+    });
+    test.is(
+      await factory.view("get_store_security_mode", { name: "carol" }),
+      null
+    );
+
+    // emergency actions
+    const pauseCall = await root
+      .call_raw(
+        factory,
+        "emergency_pause_store",
+        { store_id: aliceStore.accountId },
+        { attachedDeposit: "1", gas: Tgas(50) }
+      )
+      .catch(failPromiseRejection(test, "pausing store"));
+    assertEventLogs(
+      test,
+      (pauseCall as TransactionResult).logs,
+      [
+        {
+          standard: "mb_store",
+          version: "0.1.0",
+          event: "emergency_pause",
+          data: { store_id: aliceStore.accountId, factory_id: factory.accountId },
+        },
+      ],
+      "pausing store"
+    );
+    test.like(await aliceStore.view("get_store_settings"), {
+      minting_paused: true,
+    });
+
+    await assertContractPanics(test, [
+      [
+        async () => {
+          await alice.call(
+            factory,
+            "emergency_pause_store",
+            { store_id: aliceStore.accountId },
+            { attachedDeposit: "1", gas: Tgas(50) }
+          );
+        },
+        "Only contract owner can call this method",
+        "Non-owner tried to pause a store",
+      ],
+      [
+        async () => {
+          await alice.call(aliceStore, "emergency_pause_minting", {});
+        },
+        "Emergency actions can only be triggered via the factory",
+        "Calling emergency action directly",
+      ],
+      [
+        async () => {
+          await root.call(
+            factory,
+            "emergency_pause_store",
+            { store_id: bobStore.accountId },
+            { attachedDeposit: "1", gas: Tgas(50) }
+          );
+        },
+        "The store owner does not allow emergency actions by the factory",
+        "Pausing store that opted out",
+      ],
+    ]);
+  }
+);
@@ -0,0 +1,161 @@
+import {
+  FACTORY_WORKSPACE,
+  assertContractPanics,
+  DEPLOY_STORE_GAS,
+  DEPLOY_STORE_RENT,
+  failPromiseRejection,
+  NEAR,
+} from "./test-utils";
+
+FACTORY_WORKSPACE.test(
+  "factory::templates",
+  async (test, { root, alice, factory }) => {
+    const template = {
+      code_version: 0,
+      store_cost: NEAR(6).toString(),
+      init_args_schema: null,
+      default_init_args: JSON.stringify({ owner_id: root.accountId }),
+      enabled: true,
+    };
+
+    await root
+      .call(
+        factory,
+        "set_store_template",
+        { template_id: "basic", template },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "setting store template"));
+    test.like(await factory.view("get_store_template", { template_id: "basic" }), {
+      code_version: 0,
+      enabled: true,
+    });
+    test.is(
+      ((await factory.view("list_store_templates")) as any[]).length,
+      1
+    );
+
+    // owner from template defaults is overridden by the creator
+    await alice
+      .call(
+        factory,
+        "create_store",
+        {
+          owner_id: alice.accountId,
+          metadata: { spec: "nft-1.0.0", name: "alice", symbol: "ALICE" },
+          template_id: "basic",
+        },
+        { attachedDeposit: DEPLOY_STORE_RENT, gas: DEPLOY_STORE_GAS }
+      )
+      .catch(failPromiseRejection(test, "creating store from template"));
+    const store = factory.getFullAccount(`alice.${factory.accountId}`);
+    test.deepEqual(await store.view("list_minters"), [alice.accountId]);
+
+    // template settings are kept when factory calls are enabled on top
+    await root
+      .call(
+        factory,
+        "set_store_template",
+        {
+          template_id: "soulbound",
+          template: {
+            ...template,
+            default_init_args: JSON.stringify({
+              settings: { allow_moves: false },
+            }),
+          },
+        },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "setting soulbound template"));
+    await alice
+      .call(
+        factory,
+        "create_store",
+        {
+          owner_id: alice.accountId,
+          metadata: { spec: "nft-1.0.0", name: "sbt", symbol: "SBT" },
+          template_id: "soulbound",
+          bootstrap: { settings: { minting_paused: false } },
+          security_mode: "factory_calls",
+        },
+        { attachedDeposit: DEPLOY_STORE_RENT, gas: DEPLOY_STORE_GAS }
+      )
+      .catch(failPromiseRejection(test, "creating soulbound store"));
+    const soulbound = factory.getFullAccount(`sbt.${factory.accountId}`);
+    test.like(await soulbound.view("get_store_settings"), {
+      allow_moves: false,
+      minting_paused: false,
+      allow_factory_upgrades: true,
+      allow_factory_emergency_actions: true,
+    });
+
+    await root
+      .call(
+        factory,
+        "set_store_template_enabled",
+        { template_id: "basic", enabled: false },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "disabling store template"));
+
+    await assertContractPanics(test, [
+      // disabled templates cannot be used
+      [
+        async () => {
+          await alice.call(
+            factory,
+            "create_store",
+            {
+              owner_id: alice.accountId,
+              metadata: { spec: "nft-1.0.0", name: "bob", symbol: "BOB" },
+              template_id: "basic",
+            },
+            { attachedDeposit: DEPLOY_STORE_RENT, gas: DEPLOY_STORE_GAS }
+          );
+        },
+        "Store template basic is disabled",
+        "Creating store from disabled template",
+      ],
+      // templates need to point at uploaded code
+      [
+        async () => {
+          await root.call(
+            factory,
+            "set_store_template",
+            { template_id: "ticketing", template: { ...template, code_version: 1 } },
+            { attachedDeposit: "1" }
+          );
+        },
+        "Unknown store code version: 1",
+        "Setting template with unknown code version",
+      ],
+      // only the factory owner may manage templates
+      [
+        async () => {
+          await alice.call(
+            factory,
+            "remove_store_template",
+            { template_id: "basic" },
+            { attachedDeposit: "1" }
+          );
+        },
+        "Only contract owner can call this method",
+        "Non-owner tried to remove a store template",
+      ],
+    ]);
+
+    await root
+      .call(
+        factory,
+        "remove_store_template",
+        { template_id: "basic" },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "removing store template"));
+    test.is(
+      await factory.view("get_store_template", { template_id: "basic" }),
+      null
+    );
+  }
+);
@@ -30,7 +30,7 @@ MARKET_WORKSPACE.test(
           account_id: market.accountId,
           msg: JSON.stringify({ price: NEAR(1), autotransfer: false }),
         },
-        { attachedDeposit: mNEAR(0.81), gas: Tgas(200) }
+        { attachedDeposit: mNEAR(5), gas: Tgas(200) }
       )
       .catch(failPromiseRejection(test, "listing token"));
     const tokenKey = `0:${store.accountId}`;
@@ -37,7 +37,7 @@ MARKET_WORKSPACE.test(
           account_id: market.accountId,
           msg: JSON.stringify({ price: NEAR(1), autotransfer: true }),
         },
-        { attachedDeposit: mNEAR(8.8), gas: Tgas(200) }
+        { attachedDeposit: mNEAR(17.2), gas: Tgas(200) }
       )
       .catch(failPromiseRejection(test, "listing token"));
 
@@ -156,7 +156,7 @@ MARKET_WORKSPACE.test(
             autotransfer: true,
           }),
         },
-        { attachedDeposit: mNEAR(0.81).toString(), gas: Tgas(200) }
+        { attachedDeposit: mNEAR(5).toString(), gas: Tgas(200) }
       )
       .catch(failPromiseRejection(test, "buy now listing"));
 
@@ -244,7 +244,7 @@ MARKET_WORKSPACE.test(
       //  batch approving without tail call?
       //  -> we might and probably should require a deposit on the market for
       //     each token on offer
-      { attachedDeposit: mNEAR(8.8).toString(), gas: Tgas(200) }
+      { attachedDeposit: mNEAR(17.2).toString(), gas: Tgas(200) }
     );
 
     // check event logs
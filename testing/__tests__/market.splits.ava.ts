@@ -132,7 +132,7 @@ MARKET_WORKSPACE.test(
           account_id: market.accountId,
           msg: JSON.stringify({ price: NEAR(1), autotransfer: true }),
         },
-        { attachedDeposit: mNEAR(0.81), gas: Tgas(200) }
+        { attachedDeposit: mNEAR(5), gas: Tgas(200) }
       )
       .catch(failPromiseRejection(test, "listing token"));
     // events have been checked previously -> no need here
@@ -183,7 +183,7 @@ MARKET_WORKSPACE.test(
           account_id: market.accountId,
           msg: JSON.stringify({ price: NEAR(1), autotransfer: true }),
         },
-        { attachedDeposit: mNEAR(0.81), gas: Tgas(200) }
+        { attachedDeposit: mNEAR(5), gas: Tgas(200) }
       )
       .catch(failPromiseRejection(test, "listing token again"));
 
@@ -248,3 +248,74 @@ STORE_WORKSPACE.test(
     );
   }
 );
+
+STORE_WORKSPACE.test(
+  "ownership::storage-costs",
+  async (test, { root, alice, factory, store }) => {
+    test.like(await store.view("get_storage_costs"), {
+      storage_price_per_byte: "10000000000000000000",
+      common: uNEAR(800).toString(),
+      token: mNEAR(3.6).toString(),
+    });
+
+    // doubling the price doubles all costs
+    await alice
+      .call(
+        store,
+        "set_storage_price_per_byte",
+        { new_price: "20000000000000000000" },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "setting store storage price"));
+    test.like(await store.view("get_storage_costs"), {
+      storage_price_per_byte: "20000000000000000000",
+      common: mNEAR(1.6).toString(),
+      token: mNEAR(7.2).toString(),
+    });
+
+    await root
+      .call(
+        factory,
+        "set_storage_price_per_byte",
+        { new_price: "20000000000000000000" },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "setting factory storage price"));
+    test.is(await factory.view("get_store_cost"), NEAR(11).toString());
+    test.is(
+      await factory.view("get_minimum_attached_balance", {}),
+      NEAR(11).toString()
+    );
+  }
+);
+
+STORE_WORKSPACE.test(
+  "ownership::store-info",
+  async (test, { alice, bob, store }) => {
+    await alice
+      .call(
+        store,
+        "grant_minter",
+        { account_id: bob.accountId },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "granting minter"));
+
+    const info: any = await store.view("get_info");
+    test.like(info, {
+      owner_id: alice.accountId,
+      minter_count: 2,
+      tokens_minted: "0",
+      tokens_burned: "0",
+      settings: { allow_moves: true, minting_paused: false },
+      storage_price_per_byte: "10000000000000000000",
+      minimum_free_storage_stake: uNEAR(500).toString(),
+      reserved_mint_job_deposit: "0",
+      num_pending_mint_jobs: 0,
+    });
+    const { storage_usage } = await store.accountView();
+    test.is(info.storage_usage, storage_usage.toString());
+    test.is(info.state_version, await store.view("get_state_version"));
+    test.is(await store.view("get_owner"), alice.accountId);
+  }
+);
@@ -11,6 +11,9 @@ import {
   getBalance,
   assertContractTokenOwners,
   assertNoApproval,
+  failPromiseRejection,
+  gasBurnt,
+  Tgas,
 } from "./test-utils";
 
 const changeSettingsData = (subset: Record<string, string>) => {
@@ -67,14 +70,28 @@ STORE_WORKSPACE.test(
     );
 
     // -------------------------------- approve --------------------------------
+    const infoBefore: any = await store.view("get_info");
     const approveCall = await alice
       .call_raw(
         store,
         "nft_approve",
         { token_id: "0", account_id: bob.accountId },
-        { attachedDeposit: mNEAR(0.8) }
+        { attachedDeposit: mNEAR(5) }
       )
       .catch(failPromiseRejection("approving"));
+    // only the storage that the approval takes is charged, the remaining
+    // deposit is refunded
+    const infoAfter: any = await store.view("get_info");
+    const storageStake = new BN(infoAfter.storage_usage)
+      .sub(new BN(infoBefore.storage_usage))
+      .mul(new BN(infoAfter.storage_price_per_byte));
+    test.true(storageStake.gt(mNEAR(0.8)));
+    test.true(
+      new BN(infoAfter.balance)
+        .sub(new BN(infoBefore.balance))
+        .lt(storageStake.add(mNEAR(1))),
+      "approving did not refund the excess deposit"
+    );
     // check event logs
     assertEventLogs(
       test,
@@ -98,7 +115,7 @@ STORE_WORKSPACE.test(
             store,
             "nft_approve",
             { token_id: "1", account_id: bob.accountId },
-            { attachedDeposit: mNEAR(0.8) }
+            { attachedDeposit: mNEAR(5) }
           ),
         `${bob.accountId} is required to own token 1`,
         "Bob tried approving on unowned token",
@@ -110,9 +127,9 @@ STORE_WORKSPACE.test(
             store,
             "nft_approve",
             { token_id: "1", account_id: bob.accountId },
-            { attachedDeposit: mNEAR(0.79) }
+            { attachedDeposit: mNEAR(1) }
           ),
-        "Requires storage deposit of at least 800000000000000000000 yoctoNEAR",
+        "Requires storage deposit of at least",
         "Alice tried approving with insufficient deposit",
       ],
     ]);
@@ -146,7 +163,7 @@ STORE_WORKSPACE.test(
         store,
         "nft_batch_approve",
         { token_ids: ["1", "2"], account_id: bob.accountId },
-        { attachedDeposit: mNEAR(1.6) } // no value for this in mintbase-js
+        { attachedDeposit: mNEAR(10) } // no value for this in mintbase-js
       )
       .catch(failPromiseRejection("batch approving"));
     // check event logs
@@ -175,7 +192,7 @@ STORE_WORKSPACE.test(
             store,
             "nft_batch_approve",
             { token_ids: ["2", "3"], account_id: bob.accountId },
-            { attachedDeposit: mNEAR(1.6) }
+            { attachedDeposit: mNEAR(10) }
           ),
         `${bob.accountId} is required to own token 2`,
         "Bob tried batch approving on unowned tokens",
@@ -187,9 +204,9 @@ STORE_WORKSPACE.test(
             store,
             "nft_batch_approve",
             { token_ids: ["3"], account_id: bob.accountId },
-            { attachedDeposit: mNEAR(0.79) }
+            { attachedDeposit: mNEAR(1) }
           ),
-        "Requires storage deposit of at least 800000000000000000000 yoctoNEAR",
+        "Requires storage deposit of at least",
         "Alice tried batch approving with insufficient deposit",
       ],
     ]);
@@ -318,7 +335,7 @@ STORE_WORKSPACE.test(
       store,
       "nft_batch_approve",
       { token_ids: ["0", "1"], account_id: carol.accountId },
-      { attachedDeposit: mNEAR(1.61) } // no value for this in mintbase-js
+      { attachedDeposit: mNEAR(10) } // no value for this in mintbase-js
     );
     await assertApprovals(
       { test, store },
@@ -730,7 +747,7 @@ STORE_WORKSPACE.test(
           token_ids: ["0", "1", "2", "3"],
           account_id: bob.accountId,
         },
-        { attachedDeposit: mNEAR(3.21) } // no value for this in mintbase-js
+        { attachedDeposit: mNEAR(20) } // no value for this in mintbase-js
       )
       .catch(failPromiseRejection("approving"));
 
@@ -929,3 +946,483 @@ STORE_WORKSPACE.test(
     // ]);
   }
 );
+
+STORE_WORKSPACE.test(
+  "approvals::gas",
+  async (test, { alice, bob, carol, store }) => {
+    await batchMint({ owner: alice, store, num_to_mint: 4 }).catch(
+      failPromiseRejection(test, "minting")
+    );
+
+    // tokens 0 and 2 are approved for Bob only, tokens 1 and 3 additionally
+    // for nine other accounts
+    const approve = async (token_id: string, account_id: string) =>
+      alice
+        .call(
+          store,
+          "nft_approve",
+          { token_id, account_id },
+          { attachedDeposit: mNEAR(5) }
+        )
+        .catch(failPromiseRejection(test, "approving"));
+    for (const token_id of ["0", "1", "2", "3"]) {
+      await approve(token_id, bob.accountId);
+    }
+    for (let i = 0; i < 9; i++) {
+      await approve("1", `market-${i}.test.near`);
+      await approve("3", `market-${i}.test.near`);
+    }
+
+    // the cost of transferring a token does not depend on its approvals
+    const transferPayout = async (token_id: string) => {
+      const approval_id = await store.view("nft_approval_id", {
+        token_id,
+        account_id: bob.accountId,
+      });
+      const result = (await bob
+        .call_raw(
+          store,
+          "nft_transfer_payout",
+          {
+            receiver_id: carol.accountId,
+            token_id,
+            approval_id,
+            balance: "1000",
+            max_len_payout: 10,
+          },
+          { attachedDeposit: "1" }
+        )
+        .catch(
+          failPromiseRejection(test, "transferring with payout")
+        )) as TransactionResult;
+      return gasBurnt(result);
+    };
+    const payoutGasFew = await transferPayout("0");
+    const payoutGasMany = await transferPayout("1");
+    test.log(`nft_transfer_payout, 1 approval: ${payoutGasFew / 1e12} Tgas`);
+    test.log(`nft_transfer_payout, 10 approvals: ${payoutGasMany / 1e12} Tgas`);
+    test.true(
+      payoutGasMany - payoutGasFew < Number(Tgas(1)),
+      "approvals make `nft_transfer_payout` more expensive"
+    );
+
+    const batchTransfer = async (token_id: string) => {
+      const result = (await alice
+        .call_raw(
+          store,
+          "nft_batch_transfer",
+          { token_ids: [[token_id, carol.accountId]] },
+          { attachedDeposit: "1" }
+        )
+        .catch(failPromiseRejection(test, "batch transferring"))) as TransactionResult;
+      return gasBurnt(result);
+    };
+    const batchGasFew = await batchTransfer("2");
+    const batchGasMany = await batchTransfer("3");
+    test.log(`nft_batch_transfer, 1 approval: ${batchGasFew / 1e12} Tgas`);
+    test.log(`nft_batch_transfer, 10 approvals: ${batchGasMany / 1e12} Tgas`);
+    test.true(
+      batchGasMany - batchGasFew < Number(Tgas(1)),
+      "approvals make `nft_batch_transfer` more expensive"
+    );
+
+    // approvals are cleared by transfers
+    const token: any = await store.view("nft_token", { token_id: "3" });
+    test.deepEqual(token.approved_account_ids, {});
+  }
+);
+
+STORE_WORKSPACE.test(
+  "approvals::scoped",
+  async (test, { alice, bob, carol, store }) => {
+    await batchMint({ owner: alice, store, num_to_mint: 2 }).catch(
+      failPromiseRejection(test, "minting")
+    );
+
+    // scoped approvals require storage for the scope
+    const scope = {
+      expires_at: 4_000_000_000_000_000_000,
+      min_price: "1000",
+      single_use: true,
+    };
+    await assertContractPanics(test, [
+      [
+        async () => {
+          await alice.call(
+            store,
+            "nft_approve",
+            { token_id: "0", account_id: bob.accountId, scope },
+            { attachedDeposit: mNEAR(5) }
+          );
+        },
+        "Requires storage deposit of at least",
+        "Approving with scope without storage deposit",
+      ],
+      [
+        async () => {
+          await alice.call(
+            store,
+            "nft_approve",
+            {
+              token_id: "0",
+              account_id: bob.accountId,
+              scope: { expires_at: 1000 },
+            },
+            { attachedDeposit: mNEAR(6) }
+          );
+        },
+        "Cannot approve with an expiry in the past",
+        "Approving with expired scope",
+      ],
+    ]);
+
+    const approveCall = await alice
+      .call_raw(
+        store,
+        "nft_approve",
+        { token_id: "0", account_id: bob.accountId, scope },
+        { attachedDeposit: mNEAR(6) }
+      )
+      .catch(failPromiseRejection(test, "approving with scope"));
+    assertEventLogs(
+      test,
+      (approveCall as TransactionResult).logs,
+      [
+        {
+          standard: "mb_store",
+          version: "0.1.0",
+          event: "nft_approve",
+          data: [
+            {
+              token_id: "0",
+              approval_id: 0,
+              account_id: bob.accountId,
+              scope,
+            },
+          ],
+        },
+      ],
+      "approving with scope"
+    );
+
+    // scopes show up in token views
+    const token: any = await store.view("nft_token", { token_id: "0" });
+    test.deepEqual(token.approved_account_ids, { [bob.accountId]: 0 });
+    test.deepEqual(token.approval_scopes, { [bob.accountId]: scope });
+    test.true(
+      await store.view("nft_is_approved", {
+        token_id: "0",
+        approved_account_id: bob.accountId,
+        approval_id: 0,
+      })
+    );
+
+    // approvals with a minimum price only allow sales at that price or above
+    const transferPayout = (balance: string) =>
+      bob.call(
+        store,
+        "nft_transfer_payout",
+        {
+          receiver_id: carol.accountId,
+          token_id: "0",
+          approval_id: 0,
+          balance,
+          max_len_payout: 10,
+        },
+        { attachedDeposit: "1" }
+      );
+    await assertContractPanics(test, [
+      [
+        async () => {
+          await bob.call(
+            store,
+            "nft_transfer",
+            { receiver_id: carol.accountId, token_id: "0", approval_id: 0 },
+            { attachedDeposit: "1" }
+          );
+        },
+        `The approval of ${bob.accountId} for token 0 requires a sale for at least 1000 yoctoNEAR`,
+        "Transferring without sale",
+      ],
+      [
+        async () => {
+          await transferPayout("999");
+        },
+        `The approval of ${bob.accountId} for token 0 requires a sale for at least 1000 yoctoNEAR`,
+        "Selling below the minimum price",
+      ],
+    ]);
+    await transferPayout("1000").catch(
+      failPromiseRejection(test, "selling at the minimum price")
+    );
+    await assertContractTokenOwners(
+      { test, store },
+      [{ token_id: "0", owner_id: carol.accountId }],
+      "selling with scoped approval"
+    );
+
+    // the scope is gone with the approval
+    const sold: any = await store.view("nft_token", { token_id: "0" });
+    test.deepEqual(sold.approved_account_ids, {});
+    test.deepEqual(sold.approval_scopes, {});
+  }
+);
+
+STORE_WORKSPACE.test(
+  "approvals::single-use",
+  async (test, { root, alice, bob, store }) => {
+    await batchMint({ owner: alice, store, num_to_mint: 2 }).catch(
+      failPromiseRejection(test, "minting")
+    );
+    // returns the token to its owner for `msg: "false"`
+    const receiver = await root.createAndDeploy(
+      "receiver",
+      "../wasm/helper.wasm",
+      { method: "new", args: {} }
+    );
+
+    const approve = (token_id: string, scope: any) =>
+      alice
+        .call(
+          store,
+          "nft_approve",
+          { token_id, account_id: bob.accountId, scope },
+          { attachedDeposit: mNEAR(6) }
+        )
+        .catch(failPromiseRejection(test, "approving"));
+    const transferCall = (token_id: string, approval_id: number) =>
+      bob
+        .call(
+          store,
+          "nft_transfer_call",
+          {
+            receiver_id: receiver.accountId,
+            token_id,
+            approval_id,
+            msg: "false",
+          },
+          { attachedDeposit: "1", gas: Tgas(200) }
+        )
+        .catch(failPromiseRejection(test, "transferring with call"));
+
+    // the token stays with alice, but the single-use approval is consumed
+    await approve("0", { single_use: true });
+    await transferCall("0", 0);
+    await assertContractTokenOwners(
+      { test, store },
+      [{ token_id: "0", owner_id: alice.accountId }],
+      "returned token with single-use approval"
+    );
+    const consumed: any = await store.view("nft_token", { token_id: "0" });
+    test.deepEqual(consumed.approved_account_ids, {});
+
+    // other approvals survive the returned transfer
+    await approve("1", {});
+    await transferCall("1", 1);
+    await assertContractTokenOwners(
+      { test, store },
+      [{ token_id: "1", owner_id: alice.accountId }],
+      "returned token with regular approval"
+    );
+    const kept: any = await store.view("nft_token", { token_id: "1" });
+    test.deepEqual(kept.approved_account_ids, { [bob.accountId]: 1 });
+  }
+);
+
+STORE_WORKSPACE.test(
+  "approvals::operators",
+  async (test, { alice, bob, carol, store }) => {
+    await batchMint({ owner: alice, store, num_to_mint: 2 }).catch(
+      failPromiseRejection(test, "minting")
+    );
+
+    const approveCall = await alice
+      .call_raw(
+        store,
+        "nft_approve_for_all",
+        { account_id: bob.accountId },
+        { attachedDeposit: mNEAR(0.8) }
+      )
+      .catch(failPromiseRejection(test, "approving operator"));
+    assertEventLogs(
+      test,
+      (approveCall as TransactionResult).logs,
+      [
+        {
+          standard: "mb_store",
+          version: "0.1.0",
+          event: "nft_approve_for_all",
+          data: { owner_id: alice.accountId, account_id: bob.accountId },
+        },
+      ],
+      "approving operator"
+    );
+    test.true(
+      await store.view("nft_is_approved_for_all", {
+        owner_id: alice.accountId,
+        operator_id: bob.accountId,
+      })
+    );
+    test.deepEqual(
+      await store.view("nft_operators", { owner_id: alice.accountId }),
+      [bob.accountId]
+    );
+    test.true(
+      await store.view("nft_is_approved", {
+        token_id: "0",
+        approved_account_id: bob.accountId,
+      })
+    );
+
+    // operators transfer without approval ID
+    await bob
+      .call(
+        store,
+        "nft_transfer",
+        { receiver_id: carol.accountId, token_id: "0" },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "transferring as operator"));
+    await assertContractTokenOwners(
+      { test, store },
+      [{ token_id: "0", owner_id: carol.accountId }],
+      "transferring as operator"
+    );
+
+    // operators are bound to the owner, not to the token
+    await assertContractPanics(test, [
+      [
+        async () => {
+          await bob.call(
+            store,
+            "nft_transfer",
+            { receiver_id: bob.accountId, token_id: "0" },
+            { attachedDeposit: "1" }
+          );
+        },
+        "Disallowing approvals without approval ID",
+        "Operator transferring a token of another owner",
+      ],
+    ]);
+
+    const revokeCall = await alice
+      .call_raw(
+        store,
+        "nft_revoke_for_all",
+        { account_id: bob.accountId },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "revoking operator"));
+    assertEventLogs(
+      test,
+      (revokeCall as TransactionResult).logs,
+      [
+        {
+          standard: "mb_store",
+          version: "0.1.0",
+          event: "nft_revoke_for_all",
+          data: { owner_id: alice.accountId, account_id: bob.accountId },
+        },
+      ],
+      "revoking operator"
+    );
+    test.false(
+      await store.view("nft_is_approved_for_all", {
+        owner_id: alice.accountId,
+        operator_id: bob.accountId,
+      })
+    );
+    await assertContractPanics(test, [
+      [
+        async () => {
+          await bob.call(
+            store,
+            "nft_transfer",
+            { receiver_id: bob.accountId, token_id: "1" },
+            { attachedDeposit: "1" }
+          );
+        },
+        "Disallowing approvals without approval ID",
+        "Transferring after operator revocation",
+      ],
+    ]);
+  }
+);
+
+STORE_WORKSPACE.test(
+  "approvals::reverse-index",
+  async (test, { alice, bob, carol, store }) => {
+    await batchMint({ owner: alice, store, num_to_mint: 4 }).catch(
+      failPromiseRejection(test, "minting")
+    );
+    await alice
+      .call(
+        store,
+        "nft_batch_approve",
+        { token_ids: ["0", "1", "2"], account_id: bob.accountId },
+        { attachedDeposit: mNEAR(15) }
+      )
+      .catch(failPromiseRejection(test, "approving bob"));
+    await alice
+      .call(
+        store,
+        "nft_approve",
+        { token_id: "1", account_id: carol.accountId },
+        { attachedDeposit: mNEAR(5) }
+      )
+      .catch(failPromiseRejection(test, "approving carol"));
+
+    const approvedTokenIds = async (
+      account_id: string,
+      args: Record<string, any> = {}
+    ) => {
+      const tokens: any[] = await store.view("nft_tokens_for_approved", {
+        account_id,
+        ...args,
+      });
+      return tokens.map((token) => token.token_id);
+    };
+
+    test.is(
+      await store.view("nft_supply_for_approved", { account_id: bob.accountId }),
+      "3"
+    );
+    test.deepEqual(await approvedTokenIds(bob.accountId), ["0", "1", "2"]);
+    test.deepEqual(
+      await approvedTokenIds(bob.accountId, { after_token_id: "0", limit: 1 }),
+      ["1"]
+    );
+    test.deepEqual(await approvedTokenIds(carol.accountId), ["1"]);
+
+    // the index follows revocations and transfers
+    await alice
+      .call(
+        store,
+        "nft_revoke",
+        { token_id: "1", account_id: bob.accountId },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "revoking"));
+    test.deepEqual(await approvedTokenIds(bob.accountId), ["0", "2"]);
+
+    await alice
+      .call(store, "nft_revoke_all", { token_id: "0" }, { attachedDeposit: "1" })
+      .catch(failPromiseRejection(test, "revoking all"));
+    test.deepEqual(await approvedTokenIds(bob.accountId), ["2"]);
+
+    await alice
+      .call(
+        store,
+        "nft_transfer",
+        { receiver_id: carol.accountId, token_id: "2" },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "transferring"));
+    test.deepEqual(await approvedTokenIds(bob.accountId), []);
+    test.is(
+      await store.view("nft_supply_for_approved", { account_id: bob.accountId }),
+      "0"
+    );
+    test.deepEqual(await approvedTokenIds(carol.accountId), ["1"]);
+  }
+);
@@ -1,12 +1,18 @@
-import { TransactionResult } from "near-workspaces-ava";
+import { BN, TransactionResult } from "near-workspaces-ava";
 import {
   assertContractPanics,
   FACTORY_WORKSPACE,
+  STORE_WORKSPACE,
   DEPLOY_STORE_RENT,
   DEPLOY_STORE_GAS,
   assertContractTokenOwners,
   assertEventLogs,
+  batchMint,
   failPromiseRejection,
+  gasBurnt,
+  mNEAR,
+  NEAR,
+  Tgas,
 } from "./test-utils";
 
 FACTORY_WORKSPACE.test(
@@ -384,3 +390,470 @@ FACTORY_WORKSPACE.test(
     // TODO::testing::low: undeploy contract (store owner)
   }
 );
+
+STORE_WORKSPACE.test(
+  "core::estimate-mint-deposit",
+  async (test, { alice, bob, carol, store }) => {
+    const mintArgs = {
+      owner_id: bob.accountId,
+      metadata: { title: "estimated", reference: "some-reference" },
+      num_to_mint: 3,
+      royalty_args: {
+        split_between: { [carol.accountId]: 10000 },
+        percentage: 1000,
+      },
+      split_owners: { [bob.accountId]: 6000, [carol.accountId]: 4000 },
+    };
+    const estimate: string = await store.view("estimate_mint_deposit", {
+      ...mintArgs,
+      minter_id: alice.accountId,
+    });
+
+    const usageBefore = (await store.accountView()).storage_usage;
+    await alice
+      .call(store, "nft_batch_mint", mintArgs, { attachedDeposit: "1" })
+      .catch(failPromiseRejection(test, "minting"));
+    const usageAfter = (await store.accountView()).storage_usage;
+
+    // estimate matches the storage usage at 10^19 yoctoNEAR per byte
+    test.is(
+      estimate,
+      `${usageAfter - usageBefore}0000000000000000000`
+    );
+
+    // without accounts, the store owner is assumed to mint to themselves
+    const ownerMintArgs = {
+      owner_id: alice.accountId,
+      metadata: { title: "estimated for the owner" },
+      num_to_mint: 2,
+    };
+    const ownerEstimate: string = await store.view("estimate_mint_deposit", {
+      metadata: ownerMintArgs.metadata,
+      num_to_mint: ownerMintArgs.num_to_mint,
+    });
+    const ownerUsageBefore = (await store.accountView()).storage_usage;
+    await alice
+      .call(store, "nft_batch_mint", ownerMintArgs, { attachedDeposit: "1" })
+      .catch(failPromiseRejection(test, "minting for the owner"));
+    const ownerUsageAfter = (await store.accountView()).storage_usage;
+    test.is(
+      ownerEstimate,
+      `${ownerUsageAfter - ownerUsageBefore}0000000000000000000`
+    );
+  }
+);
+
+STORE_WORKSPACE.test(
+  "core::metadata-dedup",
+  async (test, { alice, bob, carol, store }) => {
+    const mintArgs = {
+      owner_id: bob.accountId,
+      metadata: { title: "restocked", reference: "some-reference" },
+      num_to_mint: 2,
+      royalty_args: {
+        split_between: { [carol.accountId]: 10000 },
+        percentage: 1000,
+      },
+    };
+    const mint = async () => {
+      const estimate: string = await store.view("estimate_mint_deposit", {
+        ...mintArgs,
+        minter_id: alice.accountId,
+      });
+      const usageBefore = (await store.accountView()).storage_usage;
+      await alice
+        .call(store, "nft_batch_mint", mintArgs, { attachedDeposit: "1" })
+        .catch(failPromiseRejection(test, "minting"));
+      const usageAfter = (await store.accountView()).storage_usage;
+      test.is(estimate, `${usageAfter - usageBefore}0000000000000000000`);
+      return usageAfter - usageBefore;
+    };
+
+    // restocking with identical metadata and royalty reuses their storage
+    const firstUsage = await mint();
+    const restockUsage = await mint();
+    test.true(restockUsage < firstUsage);
+    test.is(await store.view("nft_supply_for_metadata", { metadata_id: "0" }), "4");
+    test.is(
+      await store.view("get_token_remaining_copies", { token_id: "3" }),
+      4
+    );
+    const metadata: any = await store.view("nft_token_metadata", {
+      token_id: "3",
+    });
+    test.is(metadata.copies, 4);
+    test.deepEqual(
+      await store.view("get_token_royalty", { token_id: "3" }),
+      await store.view("get_token_royalty", { token_id: "0" })
+    );
+
+    // shared metadata is dropped with its last copy only
+    await bob
+      .call(
+        store,
+        "nft_batch_burn",
+        { token_ids: ["0", "1", "2"] },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "burning"));
+    test.is(
+      await store.view("get_token_remaining_copies", { token_id: "3" }),
+      1
+    );
+    await bob
+      .call(store, "nft_batch_burn", { token_ids: ["3"] }, { attachedDeposit: "1" })
+      .catch(failPromiseRejection(test, "burning"));
+
+    // minting the same metadata again stores it anew
+    test.is(await mint(), firstUsage);
+    test.is(await store.view("nft_supply_for_metadata", { metadata_id: "4" }), "2");
+  }
+);
+
+STORE_WORKSPACE.test("core::batch-gas", async (test, { alice, bob, store }) => {
+  await batchMint({ owner: alice, store, num_to_mint: 4 }).catch(
+    failPromiseRejection(test, "minting")
+  );
+
+  // batches fail before doing any work if the prepaid gas is insufficient
+  await assertContractPanics(test, [
+    [
+      async () => {
+        await alice.call(
+          store,
+          "nft_batch_mint",
+          { owner_id: alice.accountId, metadata: {}, num_to_mint: 20 },
+          { attachedDeposit: "1", gas: Tgas(20) }
+        );
+      },
+      "Not enough gas for 20 items, the prepaid gas of 20 Tgas fits at most",
+      "Minting with insufficient gas",
+    ],
+    [
+      async () => {
+        await alice.call(
+          store,
+          "nft_batch_transfer",
+          {
+            token_ids: [
+              ["0", bob.accountId],
+              ["1", bob.accountId],
+              ["2", bob.accountId],
+            ],
+          },
+          { attachedDeposit: "1", gas: Tgas(10) }
+        );
+      },
+      "Not enough gas for 3 items, the prepaid gas of 10 Tgas fits at most",
+      "Transferring with insufficient gas",
+    ],
+    [
+      async () => {
+        await alice.call(
+          store,
+          "nft_batch_burn",
+          { token_ids: ["0", "1", "2", "3"] },
+          { attachedDeposit: "1", gas: Tgas(10) }
+        );
+      },
+      "Not enough gas for 4 items, the prepaid gas of 10 Tgas fits at most",
+      "Burning with insufficient gas",
+    ],
+    [
+      async () => {
+        await alice.call(
+          store,
+          "nft_batch_approve",
+          { token_ids: ["0", "1", "2", "3"], account_id: bob.accountId },
+          { attachedDeposit: mNEAR(20), gas: Tgas(10) }
+        );
+      },
+      "Not enough gas for 4 items, the prepaid gas of 10 Tgas fits at most",
+      "Approving with insufficient gas",
+    ],
+    // the gas for `nft_on_batch_approve` is reserved on top
+    [
+      async () => {
+        await alice.call(
+          store,
+          "nft_batch_approve",
+          { token_ids: ["0"], account_id: bob.accountId, msg: "" },
+          { attachedDeposit: mNEAR(5), gas: Tgas(100) }
+        );
+      },
+      "Not enough gas for 1 items, the prepaid gas of 100 Tgas fits at most 0 items",
+      "Approving with message and insufficient gas",
+    ],
+  ]);
+  test.is(await store.view("nft_total_supply"), "4");
+  test.is(
+    await store.view("nft_supply_for_owner", { account_id: alice.accountId }),
+    "4"
+  );
+  test.deepEqual(
+    ((await store.view("nft_token", { token_id: "0" })) as any)
+      .approved_account_ids,
+    {}
+  );
+});
+
+// Per-item gas of `gas::{MINT,TRANSFER,APPROVE,BURN}_PER_TOKEN` in
+// mintbase-deps, in Tgas
+const GAS_PER_TOKEN = { mint: 2, transfer: 3, approve: 2, burn: 3 };
+
+STORE_WORKSPACE.test(
+  "core::batch-gas-per-item",
+  async (test, { alice, bob, store }) => {
+    // an owner tree of 1000 tokens, deep enough that tree operations read and
+    // write several nodes per token
+    for (let i = 0; i < 10; i++) {
+      await alice
+        .call(
+          store,
+          "nft_batch_mint",
+          { owner_id: alice.accountId, metadata: {}, num_to_mint: 100 },
+          { attachedDeposit: "1", gas: Tgas(300) }
+        )
+        .catch(failPromiseRejection(test, "minting"));
+    }
+
+    // the gas burnt per item is the difference between a batch of 21 items
+    // and a batch of one item, divided by 20
+    const perItem = async (
+      name: string,
+      batch: (items: string[]) => Promise<TransactionResult>
+    ) => {
+      const single = gasBurnt(await batch(["0"]));
+      const ids = [...Array(21).keys()].map((i) => `${i + 1}`);
+      const many = gasBurnt(await batch(ids));
+      const gas = (many - single) / 20;
+      test.log(`${name}: ${gas / 1e12} Tgas per item`);
+      return gas;
+    };
+    const batchCall =
+      (method: string, args: (ids: string[]) => any, deposit: any) =>
+      (ids: string[]) =>
+        alice
+          .call_raw(store, method, args(ids), {
+            attachedDeposit: deposit(ids.length),
+            gas: Tgas(300),
+          })
+          .catch(
+            failPromiseRejection(test, `calling ${method}`)
+          ) as Promise<TransactionResult>;
+
+    const mintGas = await perItem(
+      "nft_batch_mint",
+      batchCall(
+        "nft_batch_mint",
+        (ids) => ({
+          owner_id: alice.accountId,
+          metadata: {},
+          num_to_mint: ids.length,
+        }),
+        () => "1"
+      )
+    );
+    const approveGas = await perItem(
+      "nft_batch_approve",
+      batchCall(
+        "nft_batch_approve",
+        (ids) => ({ token_ids: ids, account_id: bob.accountId }),
+        (n) => mNEAR(5 * n)
+      )
+    );
+    const transferGas = await perItem(
+      "nft_batch_transfer",
+      batchCall(
+        "nft_batch_transfer",
+        (ids) => ({ token_ids: ids.map((id) => [id, bob.accountId]) }),
+        () => "1"
+      )
+    );
+    // burn from the large tree as well
+    const burnGas = await perItem(
+      "nft_batch_burn",
+      batchCall(
+        "nft_batch_burn",
+        (ids) => ({ token_ids: ids.map((id) => `${Number(id) + 100}`) }),
+        () => "1"
+      )
+    );
+
+    test.true(mintGas < Number(Tgas(GAS_PER_TOKEN.mint)));
+    test.true(approveGas < Number(Tgas(GAS_PER_TOKEN.approve)));
+    test.true(transferGas < Number(Tgas(GAS_PER_TOKEN.transfer)));
+    test.true(burnGas < Number(Tgas(GAS_PER_TOKEN.burn)));
+  }
+);
+
+STORE_WORKSPACE.test(
+  "core::mint-jobs",
+  async (test, { alice, bob, carol, store }) => {
+    const jobArgs = {
+      owner_id: bob.accountId,
+      metadata: { title: "large edition", reference: "some-reference" },
+      num_to_mint: 30,
+    };
+
+    await assertContractPanics(test, [
+      // only minters may create jobs
+      [
+        async () => {
+          await bob.call(store, "create_mint_job", jobArgs, {
+            attachedDeposit: NEAR(1),
+          });
+        },
+        `${bob.accountId} is not allowed to mint on this store`,
+        "Bob tried to create a mint job",
+      ],
+      // the deposit must cover the storage of all tokens
+      [
+        async () => {
+          await alice.call(store, "create_mint_job", jobArgs, {
+            attachedDeposit: "1",
+          });
+        },
+        "Creating this mint job requires a deposit of at least",
+        "Creating a mint job without deposit",
+      ],
+    ]);
+
+    const jobId = await alice
+      .call(store, "create_mint_job", jobArgs, { attachedDeposit: NEAR(1) })
+      .catch(failPromiseRejection(test, "creating mint job"));
+    test.is(jobId, "0");
+    // the deposit for the tokens is reserved until they are minted
+    const infoBefore: any = await store.view("get_info");
+    test.is(infoBefore.num_pending_mint_jobs, 1);
+    test.not(infoBefore.reserved_mint_job_deposit, "0");
+
+    // the owner cannot withdraw the deposit reserved for the job
+    await alice
+      .call(
+        store,
+        "withdraw_excess_storage_deposits",
+        {},
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "withdrawing storage deposits"));
+    const infoWithdrawn: any = await store.view("get_info");
+    test.is(
+      infoWithdrawn.reserved_mint_job_deposit,
+      infoBefore.reserved_mint_job_deposit
+    );
+    // the cushion of 0.1 NEAR remains free on top of the reserved deposit
+    test.true(
+      new BN(infoWithdrawn.free_storage_stake).gte(mNEAR(100)),
+      "withdrawing took the reserved deposit"
+    );
+
+    // the token IDs of the job are reserved for it
+    await batchMint({ owner: alice, store, num_to_mint: 1 }).catch(
+      failPromiseRejection(test, "minting")
+    );
+    const aliceTokens: any[] = await store.view("nft_tokens_for_owner", {
+      account_id: alice.accountId,
+    });
+    test.deepEqual(aliceTokens.map((token) => token.token_id), ["30"]);
+
+    // anyone may continue the job, each call mints as much as the gas allows
+    const firstChunk = await carol
+      .call_raw(
+        store,
+        "continue_mint_job",
+        { job_id: "0" },
+        { gas: Tgas(30) }
+      )
+      .catch(failPromiseRejection(test, "continuing mint job"));
+    const remaining = parseInt(
+      (firstChunk as TransactionResult).parseResult<string>()
+    );
+    test.true(remaining > 0 && remaining < 30);
+    const minted = 30 - remaining;
+    assertEventLogs(
+      test,
+      (firstChunk as TransactionResult).logs,
+      [
+        {
+          standard: "nep171",
+          version: "1.0.0",
+          event: "nft_mint",
+          data: [
+            {
+              owner_id: bob.accountId,
+              token_ids: [...Array(minted).keys()].map((id) => `${id}`),
+              memo: JSON.stringify({
+                royalty: null,
+                split_owners: null,
+                meta_id: "some-reference",
+                meta_extra: null,
+                minter: alice.accountId,
+              }),
+            },
+          ],
+        },
+      ],
+      "continuing mint job"
+    );
+    const job: any = await store.view("get_mint_job", { job_id: "0" });
+    test.is(job.minted, `${minted}`);
+    test.is(job.total, "30");
+    test.is(
+      await store.view("nft_supply_for_owner", { account_id: bob.accountId }),
+      `${minted}`
+    );
+    test.is(
+      await store.view("get_token_remaining_copies", { token_id: "0" }),
+      minted
+    );
+    const infoDuring: any = await store.view("get_info");
+    test.true(
+      new BN(infoDuring.reserved_mint_job_deposit).lt(
+        new BN(infoBefore.reserved_mint_job_deposit)
+      )
+    );
+
+    let left = remaining;
+    while (left > 0) {
+      left = parseInt(
+        await carol
+          .call(store, "continue_mint_job", { job_id: "0" }, { gas: Tgas(100) })
+          .catch(failPromiseRejection(test, "continuing mint job"))
+      );
+    }
+    test.is(
+      await store.view("nft_supply_for_owner", { account_id: bob.accountId }),
+      "30"
+    );
+    test.is(await store.view("nft_supply_for_metadata", { metadata_id: "0" }), "30");
+    test.is(
+      await store.view("get_token_remaining_copies", { token_id: "0" }),
+      30
+    );
+    const infoAfter: any = await store.view("get_info");
+    test.is(infoAfter.num_pending_mint_jobs, 0);
+    test.is(infoAfter.reserved_mint_job_deposit, "0");
+    test.is(
+      ((await store.view("get_mint_job", { job_id: "0" })) as any).minted,
+      "30"
+    );
+
+    await assertContractPanics(test, [
+      [
+        async () => {
+          await carol.call(store, "continue_mint_job", { job_id: "0" });
+        },
+        "Mint job 0 is already complete",
+        "Continuing a completed mint job",
+      ],
+      [
+        async () => {
+          await carol.call(store, "continue_mint_job", { job_id: "1" });
+        },
+        "No mint job with ID 1",
+        "Continuing an unknown mint job",
+      ],
+    ]);
+  }
+);
@@ -1,6 +1,14 @@
-import { STORE_WORKSPACE, assertTokensAre, batchMint } from "./test-utils";
+import { TransactionResult } from "near-workspaces-ava";
+import {
+  STORE_WORKSPACE,
+  assertTokensAre,
+  batchMint,
+  failPromiseRejection,
+  gasBurnt,
+  Tgas,
+} from "./test-utils";
 
-STORE_WORKSPACE.test("enumeration", async (test, { alice, bob, store }) => {
+STORE_WORKSPACE.test("enumeration", async (test, { alice, bob, carol, store }) => {
   const failPromiseRejection = (msg: string) => (e: any) => {
     test.log(`Promise rejected while ${msg}:`);
     test.log(e);
@@ -54,12 +62,9 @@ STORE_WORKSPACE.test("enumeration", async (test, { alice, bob, store }) => {
   );
 
   // call `nft_tokens` with starting index and limit
-  // FIXME::contracts::medium: according to standard, `limit` is not the
-  //  index of the last token, but the maximum number of tokens to return
   assertTokensAre(
     test,
-    // FIXME::contracts::medium: limit should be 2
-    await store.view("nft_tokens", { from_index: "1", limit: 3 }),
+    await store.view("nft_tokens", { from_index: "1", limit: 2 }),
     [
       { token_id: "1", owner_id: alice.accountId },
       { token_id: "2", owner_id: bob.accountId },
@@ -99,8 +104,6 @@ STORE_WORKSPACE.test("enumeration", async (test, { alice, bob, store }) => {
       // TODO::contracts::medium: should this index refer to token_id, or the
       //  index of token for this token owner? -> if token_id, then use "2"
       from_index: "0",
-      // Unlike `nft_tokens`, here the limit behaves according to spec
-      // (see above)
       limit: 1,
     }),
     [{ token_id: "2", owner_id: bob.accountId }],
@@ -135,8 +138,334 @@ STORE_WORKSPACE.test("enumeration", async (test, { alice, bob, store }) => {
     [{ token_id: "3", owner_id: bob.accountId }],
     "`nft_tokens_for_owner({})` output is wrong after burning"
   );
+
+  // burned tokens are not part of the supply, and paging skips them
+  test.is(await store.view("nft_total_supply", {}), "3");
+  assertTokensAre(
+    test,
+    await store.view("nft_tokens", { from_index: "2" }),
+    [{ token_id: "3", owner_id: bob.accountId }],
+    "`nft_tokens({ from_index })` output is wrong after burning"
+  );
+  assertTokensAre(
+    test,
+    await store.view("nft_tokens", { from_index: "1", limit: 1 }),
+    [{ token_id: "1", owner_id: alice.accountId }],
+    "`nft_tokens({ from_index, limit })` output is wrong after burning"
+  );
+  test.deepEqual(await store.view("nft_tokens", { from_index: "3" }), []);
+
+  // cursor-based paging skips burned tokens as well
+  assertTokensAre(
+    test,
+    await store.view("nft_tokens_after", { after_token_id: "1" }),
+    [{ token_id: "3", owner_id: bob.accountId }],
+    "`nft_tokens_after({ after_token_id })` output is wrong after burning"
+  );
+  assertTokensAre(
+    test,
+    await store.view("nft_tokens_after", { limit: 1 }),
+    [{ token_id: "0", owner_id: alice.accountId }],
+    "`nft_tokens_after({ limit })` output is wrong after burning"
+  );
+
+  // owners without tokens have an empty enumeration
+  test.is(
+    await store.view("nft_supply_for_owner", { account_id: carol.accountId }),
+    "0"
+  );
+  test.deepEqual(
+    await store.view("nft_tokens_for_owner", { account_id: carol.accountId }),
+    []
+  );
+  await bob.call(
+    store,
+    "nft_batch_burn",
+    { token_ids: ["3"] },
+    { attachedDeposit: "1" }
+  );
+  test.deepEqual(
+    await store.view("nft_tokens_for_owner", { account_id: bob.accountId }),
+    []
+  );
+  test.is(await store.view("nft_total_supply", {}), "2");
 });
 
+STORE_WORKSPACE.test(
+  "enumeration::indexes",
+  async (test, { alice, bob, carol, store }) => {
+    await alice
+      .call(
+        store,
+        "grant_minter",
+        { account_id: bob.accountId },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "granting minter"));
+    // edition 0 minted by Alice, edition 2 minted by Bob
+    await batchMint({ owner: alice, store, num_to_mint: 2 }).catch(
+      failPromiseRejection(test, "minting")
+    );
+    await batchMint({
+      owner: bob,
+      store,
+      num_to_mint: 3,
+      owner_id: carol.accountId,
+      metadata: { title: "Bob's edition" },
+    }).catch(failPromiseRejection(test, "minting"));
+
+    test.is(await store.view("nft_supply_for_metadata", { metadata_id: "2" }), "3");
+    assertTokensAre(
+      test,
+      await store.view("nft_tokens_for_metadata", {
+        metadata_id: "2",
+        after_token_id: "2",
+        limit: 1,
+      }),
+      [{ token_id: "3", owner_id: carol.accountId }],
+      "`nft_tokens_for_metadata` output is wrong"
+    );
+    test.is(
+      await store.view("nft_supply_for_minter", { minter_id: alice.accountId }),
+      "2"
+    );
+    assertTokensAre(
+      test,
+      await store.view("nft_tokens_for_minter", { minter_id: alice.accountId }),
+      [
+        { token_id: "0", owner_id: alice.accountId },
+        { token_id: "1", owner_id: alice.accountId },
+      ],
+      "`nft_tokens_for_minter` output is wrong"
+    );
+    test.is(await store.view("nft_supply_with_origin"), "0");
+    test.deepEqual(await store.view("nft_tokens_with_origin", {}), []);
+
+    // burning keeps the indexes up to date
+    await carol
+      .call(
+        store,
+        "nft_batch_burn",
+        { token_ids: ["2", "3", "4"] },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "burning"));
+    test.is(await store.view("nft_supply_for_metadata", { metadata_id: "2" }), "0");
+    test.deepEqual(
+      await store.view("nft_tokens_for_metadata", { metadata_id: "2" }),
+      []
+    );
+    test.is(
+      await store.view("nft_supply_for_minter", { minter_id: bob.accountId }),
+      "0"
+    );
+  }
+);
+
+STORE_WORKSPACE.test(
+  "enumeration::holders",
+  async (test, { alice, bob, carol, store }) => {
+    // edition 0: two tokens for Alice, one for Bob, one for Carol
+    await batchMint({ owner: alice, store, num_to_mint: 4 }).catch(
+      failPromiseRejection(test, "minting")
+    );
+    await alice
+      .call(
+        store,
+        "nft_batch_transfer",
+        { token_ids: [["2", bob.accountId], ["3", carol.accountId]] },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "transferring"));
+    // edition 4: one token for Bob
+    await batchMint({
+      owner: alice,
+      store,
+      num_to_mint: 1,
+      owner_id: bob.accountId,
+      metadata: { title: "Bob's edition" },
+    }).catch(failPromiseRejection(test, "minting"));
+
+    test.is(await store.view("nft_holders_count"), "3");
+    test.deepEqual(await store.view("nft_holders", {}), [
+      [alice.accountId, "2"],
+      [bob.accountId, "2"],
+      [carol.accountId, "1"],
+    ]);
+    test.deepEqual(
+      await store.view("nft_holders", {
+        after_account_id: alice.accountId,
+        limit: 1,
+      }),
+      [[bob.accountId, "2"]]
+    );
+    test.deepEqual(
+      await store.view("nft_holders_for_metadata", { metadata_id: "0" }),
+      [
+        [alice.accountId, "2"],
+        [bob.accountId, "1"],
+        [carol.accountId, "1"],
+      ].sort((a, b) => a[0].localeCompare(b[0]))
+    );
+    test.deepEqual(
+      await store.view("nft_holders_for_metadata", {
+        metadata_id: "0",
+        after_account_id: alice.accountId,
+        limit: 1,
+      }),
+      [[bob.accountId, "1"]]
+    );
+
+    // accounts without tokens are no longer holders
+    await carol
+      .call(store, "nft_batch_burn", { token_ids: ["3"] }, { attachedDeposit: "1" })
+      .catch(failPromiseRejection(test, "burning"));
+    await alice
+      .call(
+        store,
+        "nft_batch_transfer",
+        { token_ids: [["0", bob.accountId], ["1", bob.accountId]] },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "transferring"));
+    test.deepEqual(await store.view("nft_holders", {}), [[bob.accountId, "4"]]);
+    test.deepEqual(
+      await store.view("nft_holders_for_metadata", { metadata_id: "0" }),
+      [[bob.accountId, "3"]]
+    );
+    test.deepEqual(
+      await store.view("nft_holders_for_metadata", { metadata_id: "4" }),
+      [[bob.accountId, "1"]]
+    );
+  }
+);
+
+STORE_WORKSPACE.test(
+  "enumeration::owner-cursor",
+  async (test, { alice, bob, carol, store }) => {
+    await batchMint({ owner: alice, store, num_to_mint: 6 }).catch(
+      failPromiseRejection(test, "minting")
+    );
+
+    assertTokensAre(
+      test,
+      await store.view("nft_tokens_for_owner_after", {
+        account_id: alice.accountId,
+        limit: 2,
+      }),
+      [
+        { token_id: "0", owner_id: alice.accountId },
+        { token_id: "1", owner_id: alice.accountId },
+      ],
+      "first page is wrong"
+    );
+
+    // removing tokens between pages neither repeats nor skips tokens
+    await alice
+      .call(
+        store,
+        "nft_batch_transfer",
+        { token_ids: [["0", bob.accountId]] },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "transferring"));
+    await alice
+      .call(store, "nft_batch_burn", { token_ids: ["3"] }, { attachedDeposit: "1" })
+      .catch(failPromiseRejection(test, "burning"));
+    assertTokensAre(
+      test,
+      await store.view("nft_tokens_for_owner_after", {
+        account_id: alice.accountId,
+        after_token_id: "1",
+        limit: 2,
+      }),
+      [
+        { token_id: "2", owner_id: alice.accountId },
+        { token_id: "4", owner_id: alice.accountId },
+      ],
+      "second page is wrong"
+    );
+    assertTokensAre(
+      test,
+      await store.view("nft_tokens_for_owner_after", {
+        account_id: alice.accountId,
+        after_token_id: "4",
+        limit: 2,
+      }),
+      [{ token_id: "5", owner_id: alice.accountId }],
+      "last page is wrong"
+    );
+
+    // index-based paging is ordered by token ID as well
+    test.deepEqual(
+      await store.view("nft_tokens_for_owner_set", {
+        account_id: alice.accountId,
+      }),
+      [1, 2, 4, 5]
+    );
+    test.deepEqual(
+      await store.view("nft_tokens_for_owner_set", {
+        account_id: carol.accountId,
+      }),
+      []
+    );
+    assertTokensAre(
+      test,
+      await store.view("nft_tokens_for_owner", {
+        account_id: alice.accountId,
+        from_index: "2",
+      }),
+      [
+        { token_id: "4", owner_id: alice.accountId },
+        { token_id: "5", owner_id: alice.accountId },
+      ],
+      "`nft_tokens_for_owner({ from_index })` output is wrong"
+    );
+  }
+);
+
+STORE_WORKSPACE.test(
+  "enumeration::owner-cursor-gas",
+  async (test, { alice, store }) => {
+    // batches of 100 tokens need 205 Tgas at `gas::MINT_PER_TOKEN`
+    for (let i = 0; i < 4; i++) {
+      await alice
+        .call(
+          store,
+          "nft_batch_mint",
+          { owner_id: alice.accountId, metadata: {}, num_to_mint: 100 },
+          { attachedDeposit: "1", gas: Tgas(300) }
+        )
+        .catch(failPromiseRejection(test, "minting"));
+    }
+
+    // gas per page, for the first page and pages deep into the tree, which
+    // should not cost more than the first one
+    for (const limit of [10, 25, 50]) {
+      const pageGas = [];
+      for (const after_token_id of [undefined, "199", "389"]) {
+        const result = (await alice
+          .call_raw(store, "nft_tokens_for_owner_after", {
+            account_id: alice.accountId,
+            after_token_id,
+            limit,
+          })
+          .catch(failPromiseRejection(test, "paging tokens"))) as TransactionResult;
+        const gas = gasBurnt(result);
+        test.log(
+          `${limit} tokens after ${after_token_id ?? "start"}: ${gas / 1e12} Tgas`
+        );
+        test.true(gas < Number(Tgas(50)), "paging is too expensive");
+        pageGas.push(gas);
+      }
+      test.true(
+        Math.max(...pageGas) - pageGas[0] < Number(Tgas(1)),
+        "later pages are more expensive than the first one"
+      );
+    }
+  }
+);
+
 // TODO:
 // - [] test `nft_tokens_for_owner_set`, but only after syncing back wether it
 //      is used e.g. in mintbase-js, otherwise make it private
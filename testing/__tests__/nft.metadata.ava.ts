@@ -47,6 +47,19 @@ STORE_WORKSPACE.test("metadata", async (test, { alice, store }) => {
     extra: "No more extras for you!",
   });
 
+  // copy counts are kept up to date when burning
+  test.is(
+    await store.view("get_token_remaining_copies", { token_id: "1" }),
+    2
+  );
+  await alice
+    .call(store, "nft_batch_burn", { token_ids: ["0"] }, { attachedDeposit: "1" })
+    .catch(failPromiseRejection(test, "burning"));
+  test.is(
+    await store.view("get_token_remaining_copies", { token_id: "1" }),
+    1
+  );
+
   // TODO::testing::low: deploying with icon/base URI
   // TODO::testing::low: changing icon/base URI
 });
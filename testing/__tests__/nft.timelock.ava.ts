@@ -0,0 +1,281 @@
+import { TransactionResult } from "near-workspaces-ava";
+import {
+  assertContractPanics,
+  assertEventLogs,
+  failPromiseRejection,
+  hours,
+  STORE_WORKSPACE,
+} from "./test-utils";
+
+STORE_WORKSPACE.test("timelock", async (test, { alice, bob, store }) => {
+  test.is(await store.view("get_admin_delay"), "0");
+
+  // ------------------- scheduling with a negligible delay -------------------
+  await alice
+    .call(store, "set_admin_delay", { delay: "1" }, { attachedDeposit: "1" })
+    .catch(failPromiseRejection(test, "setting admin delay"));
+  test.is(await store.view("get_admin_delay"), "1");
+
+  const scheduleCall = await alice
+    .call_raw(
+      store,
+      "schedule_store_change",
+      { change: { SetAllowMoves: false } },
+      { attachedDeposit: "1" }
+    )
+    .catch(failPromiseRejection(test, "scheduling store change"));
+  const scheduleLog = JSON.parse(
+    (scheduleCall as TransactionResult).logs[0].slice(11)
+  );
+  test.like(
+    scheduleLog,
+    {
+      standard: "mb_store",
+      version: "0.1.0",
+      event: "schedule_change",
+      data: { change_id: "0", change: { SetAllowMoves: false } },
+    },
+    "Bad event log for scheduling store change"
+  );
+
+  const executeCall = await alice
+    .call_raw(
+      store,
+      "execute_store_change",
+      { change_id: "0" },
+      { attachedDeposit: "1" }
+    )
+    .catch(failPromiseRejection(test, "executing store change"));
+  assertEventLogs(
+    test,
+    (executeCall as TransactionResult).logs,
+    [
+      {
+        standard: "mb_store",
+        version: "0.1.0",
+        event: "execute_change",
+        data: { change_id: "0", change: { SetAllowMoves: false } },
+      },
+    ],
+    "executing store change"
+  );
+  test.deepEqual(await store.view("list_pending_store_changes"), []);
+
+  // scheduled admin delay changes are logged like immediate ones
+  await alice
+    .call(
+      store,
+      "schedule_store_change",
+      { change: { SetAdminDelay: "2" } },
+      { attachedDeposit: "1" }
+    )
+    .catch(failPromiseRejection(test, "scheduling admin delay change"));
+  const executeDelayCall = await alice
+    .call_raw(
+      store,
+      "execute_store_change",
+      { change_id: "1" },
+      { attachedDeposit: "1" }
+    )
+    .catch(failPromiseRejection(test, "executing admin delay change"));
+  assertEventLogs(
+    test,
+    (executeDelayCall as TransactionResult).logs,
+    [
+      {
+        standard: "mb_store",
+        version: "0.1.0",
+        event: "set_admin_delay",
+        data: { admin_delay: "2" },
+      },
+      {
+        standard: "mb_store",
+        version: "0.1.0",
+        event: "execute_change",
+        data: { change_id: "1", change: { SetAdminDelay: "2" } },
+      },
+    ],
+    "executing admin delay change"
+  );
+  test.is(await store.view("get_admin_delay"), "2");
+
+  // ------------------------ scheduling with a delay -------------------------
+  await alice
+    .call(
+      store,
+      "set_admin_delay",
+      { delay: hours(1).toString() },
+      { attachedDeposit: "1" }
+    )
+    .catch(failPromiseRejection(test, "increasing admin delay"));
+
+  await alice
+    .call(
+      store,
+      "schedule_store_change",
+      { change: { RevokeMinter: bob.accountId } },
+      { attachedDeposit: "1" }
+    )
+    .catch(failPromiseRejection(test, "scheduling minter revocation"));
+  const pending: any[] = await store.view("list_pending_store_changes");
+  test.is(pending.length, 1);
+  test.like(pending[0], {
+    id: "2",
+    change: { RevokeMinter: bob.accountId },
+  });
+
+  await assertContractPanics(test, [
+    // immediate changes are disallowed
+    [
+      async () => {
+        await alice.call(
+          store,
+          "set_allow_moves",
+          { state: true },
+          { attachedDeposit: "1" }
+        );
+      },
+      "Store changes are timelocked, use `schedule_store_change` instead",
+      "Changing settings while timelocked",
+    ],
+    [
+      async () => {
+        await alice.call(
+          store,
+          "set_minting_paused",
+          { paused: true },
+          { attachedDeposit: "1" }
+        );
+      },
+      "Store changes are timelocked, use `schedule_store_change` instead",
+      "Pausing minting while timelocked",
+    ],
+    [
+      async () => {
+        await alice.call(
+          store,
+          "set_default_royalty",
+          { royalty_args: null },
+          { attachedDeposit: "1" }
+        );
+      },
+      "Store changes are timelocked, use `schedule_store_change` instead",
+      "Changing default royalty while timelocked",
+    ],
+    [
+      async () => {
+        await alice.call(
+          store,
+          "set_allow_factory_upgrades",
+          { allow: true },
+          { attachedDeposit: "1" }
+        );
+      },
+      "Store changes are timelocked, use `schedule_store_change` instead",
+      "Allowing factory upgrades while timelocked",
+    ],
+    [
+      async () => {
+        await alice.call(
+          store,
+          "set_allow_factory_emergency_actions",
+          { allow: true },
+          { attachedDeposit: "1" }
+        );
+      },
+      "Store changes are timelocked, use `schedule_store_change` instead",
+      "Allowing factory emergency actions while timelocked",
+    ],
+    [
+      async () => {
+        await alice.call(
+          store,
+          "set_admin_delay",
+          { delay: "0" },
+          { attachedDeposit: "1" }
+        );
+      },
+      "Store changes are timelocked, use `schedule_store_change` instead",
+      "Decreasing admin delay while timelocked",
+    ],
+    // executing too early
+    [
+      async () => {
+        await alice.call(
+          store,
+          "execute_store_change",
+          { change_id: "2" },
+          { attachedDeposit: "1" }
+        );
+      },
+      "Change 2 cannot be executed before",
+      "Executing change before delay has passed",
+    ],
+    // require ownership
+    [
+      async () => {
+        await bob.call(
+          store,
+          "cancel_store_change",
+          { change_id: "2" },
+          { attachedDeposit: "1" }
+        );
+      },
+      "This method can only be called by the store owner",
+      "Non-owner tried to cancel a store change",
+    ],
+  ]);
+
+  await alice
+    .call(
+      store,
+      "cancel_store_change",
+      { change_id: "2" },
+      { attachedDeposit: "1" }
+    )
+    .catch(failPromiseRejection(test, "cancelling store change"));
+  test.deepEqual(await store.view("list_pending_store_changes"), []);
+});
+
+STORE_WORKSPACE.test(
+  "timelock::factory-permissions",
+  async (test, { alice, store }) => {
+    await alice
+      .call(store, "set_admin_delay", { delay: "1" }, { attachedDeposit: "1" })
+      .catch(failPromiseRejection(test, "setting admin delay"));
+
+    await alice
+      .call(
+        store,
+        "schedule_store_change",
+        { change: { SetAllowFactoryUpgrades: true } },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "scheduling factory upgrades"));
+    await alice
+      .call(
+        store,
+        "schedule_store_change",
+        { change: { SetAllowFactoryEmergencyActions: true } },
+        { attachedDeposit: "1" }
+      )
+      .catch(
+        failPromiseRejection(test, "scheduling factory emergency actions")
+      );
+    test.false(await store.view("get_allow_factory_upgrades"));
+    test.false(await store.view("get_allow_factory_emergency_actions"));
+
+    for (const change_id of ["0", "1"]) {
+      await alice
+        .call(
+          store,
+          "execute_store_change",
+          { change_id },
+          { attachedDeposit: "1" }
+        )
+        .catch(failPromiseRejection(test, `executing change ${change_id}`));
+    }
+    test.true(await store.view("get_allow_factory_upgrades"));
+    test.true(await store.view("get_allow_factory_emergency_actions"));
+  }
+);
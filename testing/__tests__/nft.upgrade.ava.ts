@@ -1,9 +1,18 @@
-import { Workspace, NearAccount } from "near-workspaces-ava";
+import * as fs from "fs";
+import { ExecutionContext } from "ava";
 import {
+  Workspace,
+  NearAccount,
+  TransactionResult,
+} from "near-workspaces-ava";
+import {
+  STORE_WORKSPACE,
+  assertContractPanics,
   batchMint,
   createAccounts,
   downloadContracts,
   failPromiseRejection,
+  gasBurnt,
   mNEAR,
   NEAR,
   Tgas,
@@ -56,14 +65,125 @@ Workspace.init().test("upgrade::mainnet", async (test, { root }) => {
     failPromiseRejection(test, "creating state")
   )) as StateSnapshot;
 
+  // enough tokens that the backfill needs several calls
+  await alice
+    .call(
+      store,
+      "nft_batch_mint",
+      { owner_id: alice.accountId, num_to_mint: 48, metadata: {} },
+      { attachedDeposit: "1", gas: Tgas(300) }
+    )
+    .catch(failPromiseRejection(test, "minting many tokens"));
+
+  // transfer gas of the deployed contract, as baseline for the upgraded one
+  const gasBefore = (await measureTransfers(test, accounts, "2", "3").catch(
+    failPromiseRejection(test, "measuring transfer gas before upgrading")
+  )) as TransferGas;
+
   // upgrade contracts
   await updateContract(store, "store");
+  await store
+    .call(store, "migrate", {
+      metadata: {
+        spec: "nft-1.0.0",
+        name: "store",
+        symbol: "ALICE",
+      },
+    })
+    .catch(failPromiseRejection(test, "migrating store"));
   test.log("updated store");
+  test.is(
+    await store.view("get_state_version"),
+    STORE_STATE_VERSION,
+    "Bad state version after migrating the store"
+  );
+  test.is(await store.view("get_admin_delay"), "0");
+
+  // indexes are backfilled in chunks, the store cannot be used until then
+  test.not(await store.view("get_migration_progress"), null);
+  await assertContractPanics(test, [
+    [
+      async () => {
+        await alice.call(
+          store,
+          "nft_transfer",
+          { receiver_id: root.accountId, token_id: "1" },
+          { attachedDeposit: "1" }
+        );
+      },
+      "This store is being migrated, call `continue_migration` first",
+      "Transferring while migrating",
+    ],
+    [
+      async () => {
+        await root.call(store, "clear_legacy_owner_sets", {
+          account_ids: [alice.accountId],
+        });
+      },
+      "This store is being migrated, call `continue_migration` first",
+      "Clearing legacy owner sets while migrating",
+    ],
+  ]);
+  const backfillCalls = (await finishMigration(store, 20).catch(
+    failPromiseRejection(test, "backfilling store")
+  )) as number;
+  test.log(`backfill took ${backfillCalls} calls`);
+  test.true(backfillCalls > 1, "Backfill completed in a single call");
+  test.is(await store.view("get_migration_progress"), null);
+  test.is(await store.view("nft_total_supply"), "50");
+  test.is(
+    await store.view("nft_supply_for_owner", { account_id: alice.accountId }),
+    "48"
+  );
+
+  const gasAfter = (await measureTransfers(test, accounts, "4", "5").catch(
+    failPromiseRejection(test, "measuring transfer gas after upgrading")
+  )) as TransferGas;
+  for (const method of ["nft_transfer_payout", "nft_batch_transfer"]) {
+    test.log(
+      `${method}: ${gasBefore[method] / 1e12} Tgas before upgrading, ` +
+        `${gasAfter[method] / 1e12} Tgas after upgrading`
+    );
+    test.true(
+      gasAfter[method] <= gasBefore[method] + TRANSFER_GAS_MARGIN,
+      `${method} takes more than ${TRANSFER_GAS_MARGIN / 1e12} Tgas ` +
+        "on top of the deployed store"
+    );
+  }
+  // owner sets of accounts without tokens may be dropped afterwards
+  await root
+    .call(store, "clear_legacy_owner_sets", { account_ids: [root.accountId] })
+    .catch(failPromiseRejection(test, "clearing legacy owner sets"));
+  await assertContractPanics(test, [
+    [
+      async () => {
+        await root.call(store, "continue_migration", { limit: 100 });
+      },
+      "There is no migration in progress",
+      "Continuing a completed migration",
+    ],
+  ]);
+
   await updateContract(factory, "factory");
   test.log("updated factory");
+  await factory
+    .call(factory, "migrate", {})
+    .catch(failPromiseRejection(test, "migrating factory"));
   await updateContract(market, "market");
   test.log("updated market");
 
+  // migrating an up-to-date store does not change its state
+  await store
+    .call(store, "migrate", {
+      metadata: {
+        spec: "nft-1.0.0",
+        name: "store",
+        symbol: "ALICE",
+      },
+    })
+    .catch(failPromiseRejection(test, "re-migrating store"));
+  test.is(await store.view("get_state_version"), STORE_STATE_VERSION);
+
   // compare pre- and post-upgrade states
   const currentState = await queryState(accounts);
 
@@ -91,8 +211,192 @@ Workspace.init().test("upgrade::mainnet", async (test, { root }) => {
     currentState.tokenData.approved_account_ids,
     referenceState.tokenData.approved_account_ids
   );
+
+  // existing approvals are indexed by approved account
+  test.is(
+    await store.view("nft_supply_for_approved", {
+      account_id: market.accountId,
+    }),
+    "1"
+  );
 });
 
+/** Needs to match `STATE_VERSION` of the store contract */
+const STORE_STATE_VERSION = 2;
+
+STORE_WORKSPACE.test("upgrade::state-version", async (test, { store }) => {
+  // freshly deployed stores start out with the current layout
+  test.is(await store.view("get_state_version"), STORE_STATE_VERSION);
+});
+
+STORE_WORKSPACE.test(
+  "upgrade::factory",
+  async (test, { root, alice, bob, factory, store }) => {
+    test.is(await factory.view("get_latest_store_version"), 0);
+    test.is(
+      await factory.view("get_store_version", { store_id: store.accountId }),
+      0
+    );
+
+    // upload the current store code as raw input
+    await root
+      .call(
+        factory,
+        "upload_store_code",
+        fs.readFileSync("../wasm/store.wasm"),
+        { attachedDeposit: NEAR(6), gas: Tgas(300) }
+      )
+      .catch(failPromiseRejection(test, "uploading store code"));
+    test.is(await factory.view("get_latest_store_version"), 1);
+
+    await assertContractPanics(test, [
+      // only the factory owner may upload code
+      [
+        async () => {
+          await alice.call(
+            factory,
+            "upload_store_code",
+            fs.readFileSync("../wasm/store.wasm"),
+            { attachedDeposit: NEAR(6), gas: Tgas(300) }
+          );
+        },
+        "Only contract owner can call this method",
+        "Non-owner tried to upload store code",
+      ],
+      // cannot upgrade to unknown versions
+      [
+        async () => {
+          await alice.call(
+            factory,
+            "upgrade_store",
+            { store_id: store.accountId, version: 2 },
+            { attachedDeposit: "1", gas: Tgas(200) }
+          );
+        },
+        "Unknown store code version: 2",
+        "Upgrading to an unknown version",
+      ],
+    ]);
+
+    // the factory owner may not upgrade once the store owner opted out
+    await alice
+      .call(
+        store,
+        "set_allow_factory_upgrades",
+        { allow: false },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "opting out of factory upgrades"));
+    await root
+      .call(
+        factory,
+        "upgrade_store",
+        { store_id: store.accountId, version: 1 },
+        { attachedDeposit: "1", gas: Tgas(200) }
+      )
+      .catch(failPromiseRejection(test, "upgrading store without consent"));
+    test.is(
+      await factory.view("get_store_version", { store_id: store.accountId }),
+      0
+    );
+
+    // the store owner may upgrade
+    await alice
+      .call(
+        factory,
+        "upgrade_store",
+        { store_id: store.accountId, version: 1 },
+        { attachedDeposit: "1", gas: Tgas(200) }
+      )
+      .catch(failPromiseRejection(test, "upgrading store"));
+    test.is(
+      await factory.view("get_store_version", { store_id: store.accountId }),
+      1
+    );
+    test.is(await store.view("get_state_version"), STORE_STATE_VERSION);
+
+    // cannot downgrade or reinstall a version
+    await assertContractPanics(test, [
+      [
+        async () => {
+          await bob.call(
+            factory,
+            "upgrade_store",
+            { store_id: store.accountId, version: 1 },
+            { attachedDeposit: "1", gas: Tgas(200) }
+          );
+        },
+        `${store.accountId} already runs version 1 of the store code`,
+        "Reinstalling a store version",
+      ],
+    ]);
+
+    // timelocked stores only accept scheduled upgrades
+    await root
+      .call(
+        factory,
+        "upload_store_code",
+        fs.readFileSync("../wasm/store.wasm"),
+        { attachedDeposit: NEAR(6), gas: Tgas(300) }
+      )
+      .catch(failPromiseRejection(test, "uploading store code again"));
+    await alice
+      .call(store, "set_admin_delay", { delay: "1" }, { attachedDeposit: "1" })
+      .catch(failPromiseRejection(test, "setting admin delay"));
+    await alice
+      .call(
+        factory,
+        "upgrade_store",
+        { store_id: store.accountId, version: 2 },
+        { attachedDeposit: "1", gas: Tgas(200) }
+      )
+      .catch(failPromiseRejection(test, "upgrading unscheduled"));
+    test.is(
+      await factory.view("get_store_version", { store_id: store.accountId }),
+      1
+    );
+
+    const code_hash = await factory.view("get_store_code_hash", {
+      version: 2,
+    });
+    await alice
+      .call(
+        store,
+        "schedule_store_change",
+        { change: { Upgrade: { code_hash } } },
+        { attachedDeposit: "1" }
+      )
+      .catch(failPromiseRejection(test, "scheduling upgrade"));
+    await assertContractPanics(test, [
+      [
+        async () => {
+          await alice.call(
+            store,
+            "execute_store_change",
+            { change_id: "0" },
+            { attachedDeposit: "1" }
+          );
+        },
+        "Upgrades are executed via `upgrade_store` on the factory",
+        "Executing upgrade on the store",
+      ],
+    ]);
+    await alice
+      .call(
+        factory,
+        "upgrade_store",
+        { store_id: store.accountId, version: 2 },
+        { attachedDeposit: "1", gas: Tgas(200) }
+      )
+      .catch(failPromiseRejection(test, "upgrading scheduled"));
+    test.is(
+      await factory.view("get_store_version", { store_id: store.accountId }),
+      2
+    );
+    test.deepEqual(await store.view("list_pending_store_changes"), []);
+  }
+);
+
 interface StateSnapshot {
   aliceDeployed: boolean;
   bobDeployed: boolean;
@@ -172,6 +476,82 @@ async function queryState(accounts: Accounts): Promise<StateSnapshot> {
   };
 }
 
+type TransferGas = Record<"nft_transfer_payout" | "nft_batch_transfer", number>;
+
+/**
+ * Gas that transfers on the upgraded store may burn on top of the deployed
+ * store, for maintaining the indexes that the upgrade adds.
+ */
+const TRANSFER_GAS_MARGIN = Number(Tgas(5));
+
+/**
+ * Gas burnt by `nft_transfer_payout` of an approved token and by
+ * `nft_batch_transfer` of a single token, both from Alice to root.
+ */
+async function measureTransfers(
+  test: ExecutionContext,
+  { root, alice, store }: Record<string, NearAccount>,
+  payoutTokenId: string,
+  batchTokenId: string
+): Promise<TransferGas> {
+  await alice.call(
+    store,
+    "nft_approve",
+    { token_id: payoutTokenId, account_id: root.accountId },
+    { attachedDeposit: mNEAR(5) }
+  );
+  const token: any = await store.view("nft_token", {
+    token_id: payoutTokenId,
+  });
+  const payout = (await root.call_raw(
+    store,
+    "nft_transfer_payout",
+    {
+      receiver_id: root.accountId,
+      token_id: payoutTokenId,
+      approval_id: token.approved_account_ids[root.accountId],
+      balance: "1000",
+      max_len_payout: 10,
+    },
+    { attachedDeposit: "1" }
+  )) as TransactionResult;
+  const batch = (await alice.call_raw(
+    store,
+    "nft_batch_transfer",
+    { token_ids: [[batchTokenId, root.accountId]] },
+    { attachedDeposit: "1" }
+  )) as TransactionResult;
+  for (const token_id of [payoutTokenId, batchTokenId]) {
+    const transferred: any = await store.view("nft_token", { token_id });
+    test.is(transferred.owner_id, root.accountId, "Transferring failed");
+  }
+  return {
+    nft_transfer_payout: gasBurnt(payout),
+    nft_batch_transfer: gasBurnt(batch),
+  };
+}
+
+/**
+ * Continues the backfill of a migrated store in chunks of `limit` items until
+ * it completes. Returns the number of calls that were needed.
+ */
+async function finishMigration(
+  store: NearAccount,
+  limit: number
+): Promise<number> {
+  let calls = 0;
+  do {
+    await store.call(
+      store,
+      "continue_migration",
+      { limit },
+      { gas: Tgas(300) }
+    );
+    calls += 1;
+  } while ((await store.view("get_migration_progress")) !== null);
+  return calls;
+}
+
 async function updateContract(contract: NearAccount, what: string) {
   const tx = await contract
     .createTransaction(contract)
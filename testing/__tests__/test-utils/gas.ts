@@ -0,0 +1,10 @@
+import { TransactionResult } from "near-workspaces-ava";
+
+/** Total gas burnt by a transaction, including all of its receipts */
+export function gasBurnt(result: TransactionResult): number {
+  const { transaction_outcome, receipts_outcome } = result.result;
+  return receipts_outcome.reduce(
+    (total, receipt) => total + receipt.outcome.gas_burnt,
+    transaction_outcome.outcome.gas_burnt
+  );
+}
@@ -11,6 +11,7 @@ export * from "./approvals";
 export * from "./events";
 export * from "./payouts";
 export * from "./download-contracts";
+export * from "./gas";
 
 // ---------------------------------- misc ---------------------------------- //
 export async function batchMint({
@@ -18,11 +19,13 @@ export async function batchMint({
   store,
   owner_id,
   num_to_mint,
+  metadata,
 }: {
   owner: NearAccount;
   store: NearAccount;
   num_to_mint: number;
   owner_id?: string;
+  metadata?: Record<string, any>;
 }) {
   if (!owner_id) owner_id = owner.accountId;
   await owner.call(
@@ -31,7 +34,7 @@ export async function batchMint({
     {
       owner_id,
       num_to_mint,
-      metadata: {},
+      metadata: metadata ?? {},
     },
     { attachedDeposit: "1" }
   );